@@ -1,13 +1,55 @@
 use tauri::AppHandle;
 use crate::error::Result;
 use crate::models::*;
-use super::{fetch_github_data, get_cached_github_data, fetch_calendar_events, get_cached_calendar_events};
+use crate::database;
+use super::{
+    fetch_github_data, get_cached_github_data, load_github_config, fetch_calendar_events, get_cached_calendar_events, get_due_calendar_tasks,
+    cached_events_between, cached_tasks_due_between,
+};
 use crate::services;
 
+// Derives the calendar-based sections of the brief from today's merged events
+async fn build_brief(app: &AppHandle, github: Option<GitHubBriefData>, calendar: Vec<CalendarEvent>) -> Result<BriefData> {
+    let tasks = get_due_calendar_tasks(app.clone()).await.unwrap_or_default();
+
+    let conn = database::open(app)?;
+    let timezone = database::get_user_timezone(&conn)?;
+    let now = chrono::Utc::now().timestamp();
+
+    // Invitations the user has not answered yet, surfaced as action items
+    let pending_invitations = calendar.iter().filter(|e| e.needs_response()).cloned().collect();
+    let schedule = services::analyze_day(&calendar, timezone, now);
+    let conflicts = services::find_conflicts(&calendar, timezone, now);
+
+    // Tomorrow's first meeting (Monday's on a Friday) and the rest of the week's deadlines
+    let next_day = services::next_working_day(timezone, now);
+    let (deadlines_start, deadlines_end) = services::deadline_range(timezone, now);
+    let lookahead = services::build_lookahead(
+        next_day,
+        &cached_events_between(&conn, next_day.0, next_day.1)?,
+        cached_tasks_due_between(&conn, deadlines_start, deadlines_end)?,
+    );
+
+    // Email is not implemented yet
+    let email = Vec::new();
+
+    Ok(BriefData {
+        github,
+        calendar,
+        pending_invitations,
+        schedule,
+        conflicts,
+        tasks,
+        lookahead,
+        email,
+        generated_at: now,
+    })
+}
+
 #[tauri::command]
 pub async fn refresh_brief(app: AppHandle) -> Result<BriefData> {
     // Check if GitHub is configured
-    let github_configured = services::get_credential("github_token")?.is_some();
+    let github_configured = !load_github_config(&database::open(&app)?)?.accounts.is_empty();
 
     // Fetch GitHub data if configured
     let github = if github_configured {
@@ -23,8 +65,8 @@ pub async fn refresh_brief(app: AppHandle) -> Result<BriefData> {
         None
     };
 
-    // Fetch calendar events
-    let calendar = match fetch_calendar_events(app.clone()).await {
+    // Fetch calendar events, leaving sources within their refresh interval cached
+    let calendar = match fetch_calendar_events(app.clone(), Some(false)).await {
         Ok(events) => events,
         Err(e) => {
             log::warn!("Failed to fetch calendar events: {}", e);
@@ -32,23 +74,13 @@ pub async fn refresh_brief(app: AppHandle) -> Result<BriefData> {
         }
     };
 
-    // Email is not implemented yet
-    let email = Vec::new();
-
-    let now = chrono::Utc::now().timestamp();
-
-    Ok(BriefData {
-        github,
-        calendar,
-        email,
-        generated_at: now,
-    })
+    build_brief(&app, github, calendar).await
 }
 
 #[tauri::command]
 pub async fn get_brief(app: AppHandle) -> Result<BriefData> {
     // Check if GitHub is configured
-    let github_configured = services::get_credential("github_token")?.is_some();
+    let github_configured = !load_github_config(&database::open(&app)?)?.accounts.is_empty();
 
     // Get cached GitHub data if configured
     let github = if github_configured {
@@ -60,15 +92,5 @@ pub async fn get_brief(app: AppHandle) -> Result<BriefData> {
     // Get cached calendar events
     let calendar = get_cached_calendar_events(app.clone()).await.unwrap_or_default();
 
-    // Email is not implemented yet
-    let email = Vec::new();
-
-    let now = chrono::Utc::now().timestamp();
-
-    Ok(BriefData {
-        github,
-        calendar,
-        email,
-        generated_at: now,
-    })
+    build_brief(&app, github, calendar).await
 }
@@ -1,67 +1,281 @@
-use tauri::{AppHandle, Manager};
+use std::collections::{HashMap, HashSet};
+use chrono::{Duration, NaiveDate};
+use tauri::AppHandle;
+use tokio::task::JoinSet;
 use rusqlite::Connection;
 use crate::error::{AppError, Result};
 use crate::models::*;
 use crate::database;
-use crate::services::CalendarService;
+use crate::services::{self, CalendarData, CalendarService, SourceFetch};
 
-fn get_db_path(app: &AppHandle) -> std::path::PathBuf {
-    let app_data = app.path().app_data_dir().expect("Failed to get app data dir");
-    std::fs::create_dir_all(&app_data).ok();
-    app_data.join("wtftoday.db")
+// Longest range get_calendar_events_in_range expands, since every source is fetched for it
+const MAX_RANGE_DAYS: i64 = 366;
+
+fn get_calendar_config(conn: &Connection) -> Result<CalendarConfig> {
+    Ok(database::get_setting(conn, "calendar_config")?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
 }
 
-fn get_connection(app: &AppHandle) -> Result<Connection> {
-    let path = get_db_path(app);
-    let conn = Connection::open(path)?;
-    database::init_database(&conn)?;
-    Ok(conn)
+fn source_cache_key(source: &CalendarSource) -> String {
+    format!("calendar:{}", source.id)
 }
 
-#[tauri::command]
-pub async fn fetch_calendar_events(app: AppHandle) -> Result<Vec<CalendarEvent>> {
-    let conn = get_connection(&app)?;
+fn store_source_data(conn: &Connection, source: &CalendarSource, data: &CalendarData, validators: &CacheValidators) -> Result<()> {
+    database::save_calendar_events(conn, &data.events, &source.id)?;
+    database::save_calendar_tasks(conn, &data.tasks, &source.id)?;
+    database::set_cache_metadata(conn, &source_cache_key(source), validators)?;
+    Ok(())
+}
 
-    // Get calendar config
-    let config: CalendarConfig = database::get_setting(&conn, "calendar_config")?
-        .and_then(|json| serde_json::from_str(&json).ok())
-        .unwrap_or_default();
+// Re-reads a single source outside the normal refresh cycle, e.g. when its file changes on disk
+pub(crate) async fn refresh_calendar_source(app: &AppHandle, source: &CalendarSource) -> Result<()> {
+    let conn = database::open(app)?;
+    let result = CalendarService::new(database::get_user_timezone(&conn)?)
+        .fetch_source(source, &CacheValidators::default())
+        .await?;
+
+    if let SourceFetch::Updated(data, validators) = result {
+        store_source_data(&conn, source, &data, &validators)?;
+    }
+    Ok(())
+}
 
-    let service = CalendarService::new();
+// Merges per-source results in config order, keeping the first copy of each event
+// (shared meetings appear in several calendars), applying source colours and the
+// user's RSVP status
+fn merge_sources(config: &CalendarConfig, mut by_source: HashMap<String, Vec<CalendarEvent>>) -> Vec<CalendarEvent> {
+    let mut seen = HashSet::new();
+    let mut merged = Vec::new();
 
-    let events = match config.source_type.as_str() {
-        "ics_url" => {
-            let url = config.ics_url.ok_or_else(|| {
-                AppError::Validation("Calendar URL not configured".to_string())
-            })?;
-            service.fetch_from_url(&url).await?
+    for source in config.enabled_sources() {
+        for mut event in by_source.remove(&source.id).unwrap_or_default() {
+            if seen.insert(event.id.clone()) {
+                event.color = source.color.clone();
+                event.my_status = services::participation_status(&event, &config.my_emails);
+                if config.hide_declined && event.is_declined() {
+                    continue;
+                }
+                merged.push(event);
+            }
         }
-        "ics_file" => {
-            let path = config.ics_path.ok_or_else(|| {
-                AppError::Validation("Calendar file path not configured".to_string())
-            })?;
-            service.parse_from_file(&path)?
+    }
+
+    services::sort_events(&mut merged);
+    merged
+}
+
+// Only enabled sources' tasks, coloured like their events. A task shared by several
+// sources is kept from the first of them in config order, like merged events.
+fn tasks_from_enabled_sources(config: &CalendarConfig, tasks: Vec<CalendarTask>) -> Vec<CalendarTask> {
+    let rank: HashMap<&str, usize> = config
+        .enabled_sources()
+        .enumerate()
+        .map(|(i, source)| (source.id.as_str(), i))
+        .collect();
+
+    let mut first_source: HashMap<String, usize> = HashMap::new();
+    for task in &tasks {
+        if let Some(&r) = rank.get(task.source.as_str()) {
+            let best = first_source.entry(task.id.clone()).or_insert(r);
+            *best = (*best).min(r);
         }
-        _ => {
-            return Ok(Vec::new());
+    }
+
+    tasks
+        .into_iter()
+        .filter_map(|mut task| {
+            let r = *rank.get(task.source.as_str())?;
+            if first_source.get(&task.id) != Some(&r) {
+                return None;
+            }
+            task.color = config.source(&task.source)?.color.clone();
+            Some(task)
+        })
+        .collect()
+}
+
+/// Cached events overlapping [start, end) from enabled sources, merged like a fetch.
+pub(crate) fn cached_events_between(conn: &Connection, start: i64, end: i64) -> Result<Vec<CalendarEvent>> {
+    let config = get_calendar_config(conn)?;
+
+    let mut by_source: HashMap<String, Vec<CalendarEvent>> = HashMap::new();
+    for event in database::get_calendar_events_between(conn, start, end)? {
+        by_source.entry(event.source.clone()).or_default().push(event);
+    }
+
+    Ok(merge_sources(&config, by_source))
+}
+
+/// Cached open tasks from enabled sources due in [start, end).
+pub(crate) fn cached_tasks_due_between(conn: &Connection, start: i64, end: i64) -> Result<Vec<CalendarTask>> {
+    let config = get_calendar_config(conn)?;
+    let timezone = database::get_user_timezone(conn)?;
+    let tasks = database::get_calendar_tasks_due_between(conn, timezone, start, end)?;
+    Ok(tasks_from_enabled_sources(&config, tasks))
+}
+
+/// Refreshes every enabled calendar source concurrently. Unless `force` is set, sources
+/// fetched within their refresh interval are served from the cache.
+#[tauri::command]
+pub async fn fetch_calendar_events(app: AppHandle, force: Option<bool>) -> Result<Vec<CalendarEvent>> {
+    let conn = database::open(&app)?;
+    let config = get_calendar_config(&conn)?;
+    let timezone = database::get_user_timezone(&conn)?;
+    let force = force.unwrap_or(true);
+    let now = chrono::Utc::now().timestamp();
+    let today = chrono::Utc::now().with_timezone(&timezone).date_naive();
+    let today_start = services::start_of_day(timezone, today);
+    let today_end = services::start_of_day(timezone, today + Duration::days(1));
+
+    let mut by_source: HashMap<String, Vec<CalendarEvent>> = HashMap::new();
+    let mut tasks = JoinSet::new();
+
+    for source in config.enabled_sources() {
+        let metadata = database::get_cache_metadata(&conn, &source_cache_key(source))?;
+        let interval = source.refresh_interval_minutes as i64 * 60;
+
+        if !force && metadata.as_ref().is_some_and(|(ts, _)| now - ts < interval) {
+            by_source.insert(source.id.clone(), database::get_calendar_events_by_source(&conn, &source.id)?);
+            continue;
         }
-    };
 
-    // Save to cache
-    database::save_calendar_events(&conn, &events, &config.source_type)?;
-    database::set_cache_metadata(&conn, "calendar", None)?;
+        // Cached events only cover the day they were expanded for, so a 304 can't be trusted
+        // once the day has rolled over
+        let validators = match metadata {
+            Some((ts, validators)) if ts >= today_start => validators,
+            _ => CacheValidators::default(),
+        };
+
+        let source = source.clone();
+        tasks.spawn(async move {
+            let service = CalendarService::new(timezone);
+            let result = service.fetch_source(&source, &validators).await;
+            (source, validators, result)
+        });
+    }
+
+    while let Some(joined) = tasks.join_next().await {
+        let (source, validators, result) = joined.map_err(|e| AppError::Other(e.to_string()))?;
+
+        let events = match result {
+            Ok(SourceFetch::Updated(data, validators)) => {
+                // Save to cache
+                store_source_data(&conn, &source, &data, &validators)?;
+                data.events
+            }
+            Ok(SourceFetch::NotModified) => {
+                database::set_cache_metadata(&conn, &source_cache_key(&source), &validators)?;
+                database::get_calendar_events_by_source(&conn, &source.id)?
+            }
+            Err(e) => {
+                log::warn!("Failed to fetch calendar source '{}': {}", source.name, e);
+                database::get_calendar_events_by_source(&conn, &source.id)?
+            }
+        };
+        by_source.insert(source.id.clone(), events);
+    }
+
+    database::set_cache_metadata(&conn, "calendar", &CacheValidators::default())?;
 
+    // Sources are expanded for the coming week, but this list is today's
+    let mut events = merge_sources(&config, by_source);
+    events.retain(|e| e.start_time < today_end && e.end_time > today_start);
     Ok(events)
 }
 
 #[tauri::command]
 pub async fn get_cached_calendar_events(app: AppHandle) -> Result<Vec<CalendarEvent>> {
-    let conn = get_connection(&app)?;
-    database::get_calendar_events_for_today(&conn)
+    let conn = database::open(&app)?;
+    let timezone = database::get_user_timezone(&conn)?;
+    let today = chrono::Utc::now().with_timezone(&timezone).date_naive();
+
+    cached_events_between(
+        &conn,
+        services::start_of_day(timezone, today),
+        services::start_of_day(timezone, today + Duration::days(1)),
+    )
+}
+
+/// Events overlapping `start_date` through `end_date` (inclusive, YYYY-MM-DD in the user's
+/// timezone). Ranges inside the cached brief window are read from the cache; any other range
+/// expands the enabled sources for just those dates, leaving the cache alone.
+#[tauri::command]
+pub async fn get_calendar_events_in_range(app: AppHandle, start_date: String, end_date: String) -> Result<Vec<CalendarEvent>> {
+    let parse = |date: &str| {
+        NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map_err(|_| AppError::Validation(format!("Invalid date '{}', expected YYYY-MM-DD", date)))
+    };
+    let (start_date, end_date) = (parse(&start_date)?, parse(&end_date)?);
+    if end_date < start_date {
+        return Err(AppError::Validation("End date is before start date".to_string()));
+    }
+    if (end_date - start_date).num_days() >= MAX_RANGE_DAYS {
+        return Err(AppError::Validation(format!("Date ranges are limited to {} days", MAX_RANGE_DAYS)));
+    }
+
+    let conn = database::open(&app)?;
+    let config = get_calendar_config(&conn)?;
+    let timezone = database::get_user_timezone(&conn)?;
+    let start = services::start_of_day(timezone, start_date);
+    let end = services::start_of_day(timezone, end_date + Duration::days(1));
+
+    let (cached_start, cached_end) = CalendarService::new(timezone).window();
+    if start >= cached_start && end <= cached_end {
+        return cached_events_between(&conn, start, end);
+    }
+
+    let mut fetches = JoinSet::new();
+    for source in config.enabled_sources() {
+        let source = source.clone();
+        fetches.spawn(async move {
+            let service = CalendarService::with_window(timezone, start, end);
+            let result = service.fetch_source(&source, &CacheValidators::default()).await;
+            (source, result)
+        });
+    }
+
+    let mut by_source: HashMap<String, Vec<CalendarEvent>> = HashMap::new();
+    let mut last_error = None;
+    while let Some(joined) = fetches.join_next().await {
+        let (source, result) = joined.map_err(|e| AppError::Other(e.to_string()))?;
+        match result {
+            Ok(SourceFetch::Updated(data, _)) => {
+                by_source.insert(source.id.clone(), data.events);
+            }
+            // Not sent any validators, so never answered with a 304
+            Ok(SourceFetch::NotModified) => {}
+            Err(e) => {
+                log::warn!("Failed to fetch calendar source '{}': {}", source.name, e);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    // Only an empty answer when there is nothing to show, not when no source could be read
+    if let (true, Some(e)) = (by_source.is_empty(), last_error) {
+        return Err(e);
+    }
+
+    let mut events = merge_sources(&config, by_source);
+    events.retain(|e| e.start_time < end && e.end_time > start);
+    Ok(events)
+}
+
+/// Open tasks from enabled sources that are due today or overdue, as of the last fetch.
+#[tauri::command]
+pub async fn get_due_calendar_tasks(app: AppHandle) -> Result<Vec<CalendarTask>> {
+    let conn = database::open(&app)?;
+    let config = get_calendar_config(&conn)?;
+    let timezone = database::get_user_timezone(&conn)?;
+
+    let tasks = database::get_due_calendar_tasks(&conn, timezone)?;
+    Ok(tasks_from_enabled_sources(&config, tasks))
 }
 
 #[tauri::command]
-pub async fn parse_ics_file(path: String) -> Result<Vec<CalendarEvent>> {
-    let service = CalendarService::new();
-    service.parse_from_file(&path)
+pub async fn parse_ics_file(app: AppHandle, path: String) -> Result<Vec<CalendarEvent>> {
+    let conn = database::open(&app)?;
+    let service = CalendarService::new(database::get_user_timezone(&conn)?);
+    Ok(service.parse_from_file(&path)?.events)
 }
@@ -1,87 +1,377 @@
-use tauri::{AppHandle, Manager};
+use std::collections::HashMap;
+use std::future::Future;
+use tauri::AppHandle;
+use tokio::task::JoinSet;
 use rusqlite::Connection;
 use crate::error::{AppError, Result};
 use crate::models::*;
 use crate::database;
-use crate::services;
+use crate::services::{self, GitHubService, NotificationsFetch};
 
-fn get_db_path(app: &AppHandle) -> std::path::PathBuf {
-    let app_data = app.path().app_data_dir().expect("Failed to get app data dir");
-    std::fs::create_dir_all(&app_data).ok();
-    app_data.join("wtftoday.db")
+// cache_metadata key holding an account's notifications Last-Modified and poll interval
+fn notifications_cache_key(account_id: &str) -> String {
+    format!("github_notifications:{}", account_id)
 }
 
-fn get_connection(app: &AppHandle) -> Result<Connection> {
-    let path = get_db_path(app);
-    let conn = Connection::open(path)?;
-    database::init_database(&conn)?;
-    Ok(conn)
+/// Configured GitHub accounts. A token saved before accounts existed becomes the first one.
+pub(crate) fn load_github_config(conn: &Connection) -> Result<GitHubConfig> {
+    if let Some(json) = database::get_setting(conn, "github_config")? {
+        return Ok(serde_json::from_str(&json).unwrap_or_default());
+    }
+
+    let mut config = GitHubConfig::default();
+    if let Some(token) = services::get_credential("github_token")? {
+        let account = GitHubAccount {
+            id: "default".to_string(),
+            name: "GitHub".to_string(),
+            username: database::get_setting(conn, "github_username")?,
+            endpoints: database::get_setting(conn, "github_endpoints")?
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+            oauth_client_id: None,
+            token_expires_at: None,
+        };
+        services::save_credential(&services::github_credential_key(&account.id), &token)?;
+        services::delete_credential("github_token")?;
+        config.accounts.push(account);
+    }
+
+    save_github_config(conn, &config)?;
+    Ok(config)
 }
 
-#[tauri::command]
-pub async fn fetch_github_data(app: AppHandle) -> Result<GitHubBriefData> {
-    // Get the GitHub token
-    let token = services::get_credential("github_token")?
-        .ok_or_else(|| AppError::NotFound("GitHub token not configured".to_string()))?;
-
-    let service = services::GitHubService::new(token)?;
-
-    // Get current user
-    let user = service.get_current_user().await?;
-    let username = &user.login;
-
-    // Store username for settings display
-    let conn = get_connection(&app)?;
-    database::set_setting(&conn, "github_username", username)?;
-
-    // Fetch all data in parallel
-    let (prs_to_review, my_open_prs, mentioned_issues, notifications) = tokio::join!(
-        service.get_prs_to_review(username),
-        service.get_my_open_prs(username),
-        service.get_mentioned_issues(username),
-        service.get_notifications()
-    );
+pub(crate) fn save_github_config(conn: &Connection, config: &GitHubConfig) -> Result<()> {
+    database::set_setting(conn, "github_config", &serde_json::to_string(config).unwrap_or_default())
+}
 
-    let prs_to_review = prs_to_review.unwrap_or_default();
-    let my_open_prs = my_open_prs.unwrap_or_default();
-    let mentioned_issues = mentioned_issues.unwrap_or_default();
-    let notifications = notifications.unwrap_or_default();
+/// Stores the token GitHub granted an OAuth sign-in and when it expires. The caller saves the
+/// account's config.
+pub(crate) fn store_github_token(account: &mut GitHubAccount, token: &GitHubOAuthToken, now: i64) -> Result<()> {
+    let access_token = token
+        .access_token
+        .as_deref()
+        .ok_or_else(|| AppError::Parse("GitHub returned no access token".to_string()))?;
+    services::save_credential(&services::github_credential_key(&account.id), access_token)?;
 
-    // Save to cache
-    database::save_github_prs(&conn, &prs_to_review, "review")?;
-    database::save_github_prs(&conn, &my_open_prs, "mine")?;
-    database::save_github_issues(&conn, &mentioned_issues)?;
-    database::save_github_notifications(&conn, &notifications)?;
-    database::set_cache_metadata(&conn, "github", None)?;
+    account.token_expires_at = token.expires_in.map(|secs| now + secs);
+    Ok(())
+}
 
-    let now = chrono::Utc::now().timestamp();
+fn account_service(account: &GitHubAccount) -> Result<GitHubService> {
+    // Expired tokens can't be refreshed without the OAuth app's secret, so the device flow
+    // has to run again
+    if account.token_expires_at.is_some_and(|expires_at| expires_at <= chrono::Utc::now().timestamp()) {
+        return Err(AppError::Validation(format!(
+            "GitHub account '{}' needs to sign in again: its sign-in expired",
+            account.name
+        )));
+    }
 
-    Ok(GitHubBriefData {
+    let token = services::get_credential(&services::github_credential_key(&account.id))?
+        .ok_or_else(|| AppError::NotFound(format!("No token stored for GitHub account '{}'", account.name)))?;
+    Ok(GitHubService::new(token)?.with_account(account))
+}
+
+fn github_service(app: &AppHandle, account_id: &str) -> Result<GitHubService> {
+    let config = load_github_config(&database::open(app)?)?;
+    let account = config
+        .account(account_id)
+        .ok_or_else(|| AppError::NotFound(format!("GitHub account '{}'", account_id)))?;
+    account_service(account)
+}
+
+// Rate-limit errors only carry the reset timestamp; says when that is in the user's timezone
+fn with_reset_time(conn: &Connection, error: AppError) -> AppError {
+    let AppError::RateLimited(until) = error else {
+        return error;
+    };
+    let reset = database::get_user_timezone(conn)
+        .ok()
+        .zip(chrono::DateTime::from_timestamp(until, 0))
+        .map(|(tz, t)| t.with_timezone(&tz).format("%H:%M").to_string());
+    match reset {
+        Some(time) => AppError::Other(format!("GitHub rate limit exceeded until {}", time)),
+        None => error,
+    }
+}
+
+fn get_github_totals(conn: &Connection) -> Result<HashMap<String, GitHubTotals>> {
+    Ok(database::get_setting(conn, "github_totals")?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+fn store_overview(conn: &Connection, account: &GitHubAccount, overview: GitHubOverview) -> Result<()> {
+    let GitHubOverview {
+        user,
         prs_to_review,
         my_open_prs,
         mentioned_issues,
-        notifications,
-        last_updated: Some(now),
-    })
+    } = overview;
+
+    // Keep the username current for settings display
+    if account.username.as_deref() != Some(user.login.as_str()) {
+        let mut config = load_github_config(conn)?;
+        if let Some(stored) = config.accounts.iter_mut().find(|a| a.id == account.id) {
+            stored.username = Some(user.login);
+            save_github_config(conn, &config)?;
+        }
+    }
+
+    let mut totals = get_github_totals(conn)?;
+    totals.insert(
+        account.id.clone(),
+        GitHubTotals {
+            prs_to_review: prs_to_review.total_count,
+            my_open_prs: my_open_prs.total_count,
+            mentioned_issues: mentioned_issues.total_count,
+        },
+    );
+
+    database::save_github_prs(conn, &account.id, &prs_to_review.items, "review")?;
+    database::save_github_prs(conn, &account.id, &my_open_prs.items, "mine")?;
+    database::save_github_issues(conn, &account.id, &mentioned_issues.items)?;
+    database::set_setting(conn, "github_totals", &serde_json::to_string(&totals).unwrap_or_default())?;
+    Ok(())
 }
 
-#[tauri::command]
-pub async fn get_cached_github_data(app: AppHandle) -> Result<GitHubBriefData> {
-    let conn = get_connection(&app)?;
+fn cached_github_data(conn: &Connection) -> Result<GitHubBriefData> {
+    let config = load_github_config(conn)?;
+    let totals_by_account = get_github_totals(conn)?;
+
+    let accounts: Vec<GitHubAccountSummary> = config
+        .accounts
+        .into_iter()
+        .map(|account| GitHubAccountSummary {
+            totals: totals_by_account.get(&account.id).cloned().unwrap_or_default(),
+            id: account.id,
+            name: account.name,
+            username: account.username,
+        })
+        .collect();
 
-    let prs_to_review = database::get_github_prs(&conn, "review")?;
-    let my_open_prs = database::get_github_prs(&conn, "mine")?;
-    let mentioned_issues = database::get_github_issues(&conn)?;
-    let notifications = database::get_github_notifications(&conn)?;
+    let mut totals = GitHubTotals::default();
+    for account in &accounts {
+        totals += account.totals.clone();
+    }
 
-    let last_updated = database::get_cache_metadata(&conn, "github")?
+    let last_updated = database::get_cache_metadata(conn, "github")?
         .map(|(ts, _)| ts);
 
     Ok(GitHubBriefData {
-        prs_to_review,
-        my_open_prs,
-        mentioned_issues,
-        notifications,
+        prs_to_review: database::get_github_prs(conn, "review")?,
+        my_open_prs: database::get_github_prs(conn, "mine")?,
+        mentioned_issues: database::get_github_issues(conn)?,
+        notifications: database::get_github_notifications(conn)?,
+        totals,
+        accounts,
         last_updated,
+        rate_limited_until: services::github_rate_limited_until(chrono::Utc::now().timestamp()),
     })
 }
+
+/// Refreshes every GitHub account concurrently. An account that fails keeps its cached data;
+/// the refresh fails when every account did, or when there is none.
+#[tauri::command]
+pub async fn fetch_github_data(app: AppHandle) -> Result<GitHubBriefData> {
+    let conn = database::open(&app)?;
+    let config = load_github_config(&conn)?;
+    let max_items = database::get_github_max_items(&conn)?;
+    let now = chrono::Utc::now().timestamp();
+
+    let mut fetched = 0;
+    let mut last_error = None;
+
+    let mut tasks = JoinSet::new();
+    for account in &config.accounts {
+        let service = match account_service(account) {
+            Ok(service) => service.with_max_items(max_items),
+            Err(e) => {
+                log::warn!("Skipping GitHub account '{}': {}", account.name, e);
+                last_error = Some(e);
+                continue;
+            }
+        };
+
+        let metadata = database::get_cache_metadata(&conn, &notifications_cache_key(&account.id))?;
+        // GitHub asks clients to wait X-Poll-Interval seconds between notification polls
+        let poll_due = !matches!(&metadata, Some((ts, v)) if v.poll_interval.is_some_and(|interval| now - ts < interval));
+        let validators = metadata.map(|(_, v)| v).unwrap_or_default();
+
+        let account = account.clone();
+        tasks.spawn(async move {
+            // Notifications have no GraphQL API, so they are fetched alongside the overview
+            let poll_notifications = async {
+                if poll_due {
+                    Some(service.get_notifications(&validators).await)
+                } else {
+                    None
+                }
+            };
+            let (overview, notifications) = tokio::join!(service.get_overview(), poll_notifications);
+            (account, overview, notifications)
+        });
+    }
+
+    while let Some(joined) = tasks.join_next().await {
+        let (account, overview, notifications) = joined.map_err(|e| AppError::Other(e.to_string()))?;
+
+        match overview {
+            Ok(overview) => {
+                store_overview(&conn, &account, overview)?;
+                fetched += 1;
+            }
+            Err(e) => {
+                log::warn!("Failed to fetch GitHub account '{}': {}", account.name, e);
+                last_error = Some(e);
+            }
+        }
+
+        let cache_key = notifications_cache_key(&account.id);
+        match notifications {
+            Some(Ok(NotificationsFetch::Updated(notifications, validators))) => {
+                database::save_github_notifications(&conn, &account.id, &notifications)?;
+                database::set_cache_metadata(&conn, &cache_key, &validators)?;
+            }
+            Some(Ok(NotificationsFetch::NotModified(validators))) => {
+                database::set_cache_metadata(&conn, &cache_key, &validators)?;
+            }
+            Some(Err(e)) => {
+                log::warn!("Failed to fetch GitHub notifications for '{}': {}", account.name, e);
+            }
+            None => {}
+        }
+    }
+
+    // Stamping the cache as fresh would hide that nothing was refreshed
+    if fetched == 0 {
+        return Err(match last_error {
+            Some(e) => with_reset_time(&conn, e),
+            None => AppError::Validation("No GitHub account to fetch".to_string()),
+        });
+    }
+
+    database::set_cache_metadata(&conn, "github", &CacheValidators::default())?;
+    cached_github_data(&conn)
+}
+
+#[tauri::command]
+pub async fn get_cached_github_data(app: AppHandle) -> Result<GitHubBriefData> {
+    let conn = database::open(&app)?;
+    cached_github_data(&conn)
+}
+
+/// Remaining GitHub API budget per account and resource. Asks GitHub for every account with a
+/// token and otherwise returns the last values seen.
+#[tauri::command]
+pub async fn get_github_rate_limits(app: AppHandle) -> Result<Vec<GitHubRateLimit>> {
+    let conn = database::open(&app)?;
+    for account in load_github_config(&conn)?.accounts {
+        let refreshed = match account_service(&account) {
+            Ok(service) => service.refresh_rate_limits().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = refreshed {
+            log::warn!("Failed to fetch GitHub rate limits for '{}': {}", account.name, e);
+        }
+    }
+    Ok(services::github_rate_limits())
+}
+
+// Applies `update` to the cached notifications before GitHub confirms, so the brief reflects
+// the change straight away, and puts the account's cache back if the request fails
+async fn triage_notifications(
+    conn: Connection,
+    account_id: &str,
+    update: impl FnOnce(&Connection) -> Result<()>,
+    request: impl Future<Output = Result<()>>,
+) -> Result<()> {
+    let previous: Vec<GitHubNotification> = database::get_github_notifications(&conn)?
+        .into_iter()
+        .filter(|n| n.account == account_id)
+        .collect();
+    update(&conn)?;
+
+    if let Err(e) = request.await {
+        database::save_github_notifications(&conn, account_id, &previous)?;
+        return Err(with_reset_time(&conn, e));
+    }
+    Ok(())
+}
+
+fn validate_thread_id(thread_id: &str) -> Result<()> {
+    if thread_id.is_empty() || !thread_id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(AppError::Validation(format!("Invalid notification thread '{}'", thread_id)));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn mark_github_notification_read(app: AppHandle, account_id: String, thread_id: String) -> Result<()> {
+    validate_thread_id(&thread_id)?;
+    let service = github_service(&app, &account_id)?;
+    let conn = database::open(&app)?;
+
+    triage_notifications(
+        conn,
+        &account_id,
+        |conn| database::mark_github_notification_read(conn, &account_id, &thread_id),
+        service.mark_thread_read(&thread_id),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn mark_github_notification_done(app: AppHandle, account_id: String, thread_id: String) -> Result<()> {
+    validate_thread_id(&thread_id)?;
+    let service = github_service(&app, &account_id)?;
+    let conn = database::open(&app)?;
+
+    triage_notifications(
+        conn,
+        &account_id,
+        |conn| database::delete_github_notification(conn, &account_id, &thread_id),
+        service.mark_thread_done(&thread_id),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn mark_github_repo_notifications_read(app: AppHandle, account_id: String, repo_full_name: String) -> Result<()> {
+    let valid = repo_full_name
+        .split_once('/')
+        .is_some_and(|(owner, repo)| !owner.is_empty() && !repo.is_empty() && !repo.contains('/'));
+    if !valid {
+        return Err(AppError::Validation(format!("Invalid repository '{}'", repo_full_name)));
+    }
+    let service = github_service(&app, &account_id)?;
+    let conn = database::open(&app)?;
+
+    triage_notifications(
+        conn,
+        &account_id,
+        |conn| database::mark_github_repo_notifications_read(conn, &account_id, &repo_full_name),
+        service.mark_repo_notifications_read(&repo_full_name),
+    )
+    .await
+}
+
+/// Unsubscribes from a thread and marks it read, so it leaves the brief until someone
+/// mentions the user again.
+#[tauri::command]
+pub async fn unsubscribe_github_notification(app: AppHandle, account_id: String, thread_id: String) -> Result<()> {
+    validate_thread_id(&thread_id)?;
+    let service = github_service(&app, &account_id)?;
+    let conn = database::open(&app)?;
+
+    triage_notifications(
+        conn,
+        &account_id,
+        |conn| database::mark_github_notification_read(conn, &account_id, &thread_id),
+        async {
+            service.unsubscribe_thread(&thread_id).await?;
+            service.mark_thread_read(&thread_id).await
+        },
+    )
+    .await
+}
@@ -1,74 +1,330 @@
-use tauri::{AppHandle, Manager};
-use rusqlite::Connection;
-use crate::error::Result;
+use tauri::AppHandle;
+use crate::error::{AppError, Result};
 use crate::models::*;
 use crate::database;
 use crate::services;
 
-fn get_db_path(app: &AppHandle) -> std::path::PathBuf {
-    let app_data = app.path().app_data_dir().expect("Failed to get app data dir");
-    std::fs::create_dir_all(&app_data).ok();
-    app_data.join("wtftoday.db")
-}
-
-fn get_connection(app: &AppHandle) -> Result<Connection> {
-    let path = get_db_path(app);
-    let conn = Connection::open(path)?;
-    database::init_database(&conn)?;
-    Ok(conn)
-}
-
 #[tauri::command]
 pub async fn is_onboarding_complete(app: AppHandle) -> Result<bool> {
-    let conn = get_connection(&app)?;
+    let conn = database::open(&app)?;
     let value = database::get_setting(&conn, "onboarding_complete")?;
     Ok(value.map(|v| v == "true").unwrap_or(false))
 }
 
 #[tauri::command]
 pub async fn get_setting(app: AppHandle, key: String) -> Result<Option<String>> {
-    let conn = get_connection(&app)?;
+    let conn = database::open(&app)?;
     database::get_setting(&conn, &key)
 }
 
 #[tauri::command]
 pub async fn set_setting(app: AppHandle, key: String, value: String) -> Result<()> {
-    let conn = get_connection(&app)?;
+    let conn = database::open(&app)?;
     database::set_setting(&conn, &key, &value)
 }
 
-#[tauri::command]
-pub async fn save_github_token(token: String) -> Result<()> {
-    services::save_credential("github_token", &token)
+// Where to reach GitHub: github.com when `api_url` is empty, otherwise a REST API base such
+// as https://ghe.example.com/api/v3 or just a server's address. `graphql_url` is only needed
+// when the server doesn't serve GraphQL at the usual place.
+fn resolve_github_endpoints(api_url: Option<String>, graphql_url: Option<String>) -> Result<GitHubEndpoints> {
+    let api_url = api_url.unwrap_or_default();
+    let api_url = api_url.trim().trim_end_matches('/');
+    let mut endpoints = if api_url.is_empty() {
+        GitHubEndpoints::default()
+    } else {
+        let url = parse_http_url(api_url)?;
+        match url.host_str() {
+            Some("github.com") | Some("api.github.com") => GitHubEndpoints::default(),
+            // A bare server address means its REST API under /api/v3
+            _ if url.path() == "/" => GitHubEndpoints::for_api_url(&format!("{}api/v3", url)),
+            _ => GitHubEndpoints::for_api_url(url.as_str()),
+        }
+    };
+
+    if let Some(graphql_url) = graphql_url.filter(|u| !u.trim().is_empty()) {
+        endpoints.graphql_url = parse_http_url(graphql_url.trim())?.to_string();
+    }
+    Ok(endpoints)
+}
+
+// Accepts "ghe.example.com" as shorthand for https://ghe.example.com
+fn parse_http_url(value: &str) -> Result<reqwest::Url> {
+    let value = if value.contains("://") { value.to_string() } else { format!("https://{}", value) };
+    let invalid = || AppError::Validation(format!("Invalid GitHub URL '{}'", value));
+
+    let url = reqwest::Url::parse(&value).map_err(|_| invalid())?;
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return Err(invalid());
+    }
+    Ok(url)
 }
 
 #[tauri::command]
-pub async fn validate_github_token(token: String) -> Result<GitHubUser> {
-    let service = services::GitHubService::new(token)?;
+pub async fn validate_github_token(token: String, api_url: Option<String>, graphql_url: Option<String>) -> Result<GitHubUser> {
+    let service = services::GitHubService::new(token)?.with_endpoints(resolve_github_endpoints(api_url, graphql_url)?);
     service.get_current_user().await
 }
 
+/// Checks the token against its server and adds the account under `name` ("Personal", "Work").
+#[tauri::command]
+pub async fn add_github_account(
+    app: AppHandle,
+    name: String,
+    token: String,
+    api_url: Option<String>,
+    graphql_url: Option<String>,
+) -> Result<GitHubAccount> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation("GitHub account needs a name".to_string()));
+    }
+
+    let endpoints = resolve_github_endpoints(api_url, graphql_url)?;
+    let user = services::GitHubService::new(token.clone())?
+        .with_endpoints(endpoints.clone())
+        .get_current_user()
+        .await?;
+
+    let conn = database::open(&app)?;
+    let mut config = super::load_github_config(&conn)?;
+
+    let id = github_account_id(&user.login, &endpoints);
+    if config.account(&id).is_some() {
+        return Err(AppError::Validation(format!("GitHub account @{} is already added", user.login)));
+    }
+
+    let account = GitHubAccount {
+        id,
+        name: name.to_string(),
+        username: Some(user.login),
+        endpoints,
+        oauth_client_id: None,
+        token_expires_at: None,
+    };
+    services::save_credential(&services::github_credential_key(&account.id), &token)?;
+    config.accounts.push(account.clone());
+    super::save_github_config(&conn, &config)?;
+    Ok(account)
+}
+
+// One account per login and server, e.g. "octocat@github.com"
+fn github_account_id(login: &str, endpoints: &GitHubEndpoints) -> String {
+    let host = endpoints.web_url();
+    format!("{}@{}", login, host.split_once("://").map_or(host.as_str(), |(_, host)| host))
+}
+
+// The OAuth app to sign in with: the one given, or on github.com the app built in with
+// GITHUB_OAUTH_CLIENT_ID
+fn github_oauth_client_id(endpoints: &GitHubEndpoints, client_id: Option<String>) -> Result<String> {
+    if let Some(client_id) = client_id.map(|id| id.trim().to_string()).filter(|id| !id.is_empty()) {
+        return Ok(client_id);
+    }
+    GITHUB_OAUTH_CLIENT_ID
+        .filter(|_| *endpoints == GitHubEndpoints::default())
+        .map(str::to_string)
+        .ok_or_else(|| {
+            AppError::Validation(format!(
+                "Signing in needs the client ID of an OAuth app with device flow enabled. Register one \
+                 under Settings > Developer settings > OAuth Apps on {} and enter its client ID, or use \
+                 a personal access token",
+                endpoints.web_url()
+            ))
+        })
+}
+
+/// Starts signing in with GitHub's device flow. Show the user code and verification URI,
+/// then call poll_github_device_flow every `interval` seconds.
+#[tauri::command]
+pub async fn start_github_device_flow(
+    api_url: Option<String>,
+    graphql_url: Option<String>,
+    client_id: Option<String>,
+) -> Result<GitHubDeviceCode> {
+    let endpoints = resolve_github_endpoints(api_url, graphql_url)?;
+    let client_id = github_oauth_client_id(&endpoints, client_id)?;
+    services::GitHubOAuth::new(&endpoints, &client_id)?
+        .request_device_code()
+        .await
+}
+
+/// Checks whether the user finished signing in and, once they have, adds the account under
+/// `name`. Signing in as an account that is already there replaces its tokens instead.
+#[tauri::command]
+pub async fn poll_github_device_flow(
+    app: AppHandle,
+    name: String,
+    device_code: String,
+    api_url: Option<String>,
+    graphql_url: Option<String>,
+    client_id: Option<String>,
+) -> Result<GitHubDeviceFlowPoll> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation("GitHub account needs a name".to_string()));
+    }
+
+    let endpoints = resolve_github_endpoints(api_url, graphql_url)?;
+    let client_id = github_oauth_client_id(&endpoints, client_id)?;
+    let token = match services::GitHubOAuth::new(&endpoints, &client_id)?
+        .poll_device_token(&device_code)
+        .await?
+    {
+        services::DeviceFlowPoll::Granted(token) => token,
+        services::DeviceFlowPoll::Pending => return Ok(GitHubDeviceFlowPoll { account: None, interval: None }),
+        services::DeviceFlowPoll::SlowDown(interval) => {
+            return Ok(GitHubDeviceFlowPoll { account: None, interval: Some(interval) });
+        }
+    };
+
+    let access_token = token.access_token.clone().unwrap_or_default();
+    let user = services::GitHubService::new(access_token)?
+        .with_endpoints(endpoints.clone())
+        .get_current_user()
+        .await?;
+
+    let conn = database::open(&app)?;
+    let mut config = super::load_github_config(&conn)?;
+    let id = github_account_id(&user.login, &endpoints);
+    let index = match config.accounts.iter().position(|a| a.id == id) {
+        Some(index) => index,
+        None => {
+            config.accounts.push(GitHubAccount {
+                id,
+                name: name.to_string(),
+                username: Some(user.login),
+                endpoints,
+                oauth_client_id: None,
+                token_expires_at: None,
+            });
+            config.accounts.len() - 1
+        }
+    };
+
+    let account = &mut config.accounts[index];
+    account.oauth_client_id = Some(client_id);
+    super::store_github_token(account, &token, chrono::Utc::now().timestamp())?;
+    let account = account.clone();
+
+    super::save_github_config(&conn, &config)?;
+    Ok(GitHubDeviceFlowPoll { account: Some(account), interval: None })
+}
+
+/// Replaces an account's token, e.g. after it expired. The token must belong to the same user.
+#[tauri::command]
+pub async fn save_github_token(app: AppHandle, account_id: String, token: String) -> Result<()> {
+    let mut config = super::load_github_config(&database::open(&app)?)?;
+    let account = config
+        .accounts
+        .iter_mut()
+        .find(|a| a.id == account_id)
+        .ok_or_else(|| AppError::NotFound(format!("GitHub account '{}'", account_id)))?;
+
+    let user = services::GitHubService::new(token.clone())?
+        .with_endpoints(account.endpoints.clone())
+        .get_current_user()
+        .await?;
+    if account.username.as_deref().is_some_and(|username| username != user.login) {
+        return Err(AppError::Validation(format!(
+            "Token belongs to @{}, not @{}",
+            user.login,
+            account.username.as_deref().unwrap_or_default()
+        )));
+    }
+
+    services::save_credential(&services::github_credential_key(&account.id), &token)?;
+
+    // A personal access token replaces any OAuth sign-in and doesn't expire
+    account.oauth_client_id = None;
+    account.token_expires_at = None;
+    super::save_github_config(&database::open(&app)?, &config)
+}
+
+#[tauri::command]
+pub async fn remove_github_account(app: AppHandle, account_id: String) -> Result<()> {
+    let conn = database::open(&app)?;
+    let mut config = super::load_github_config(&conn)?;
+    config.accounts.retain(|a| a.id != account_id);
+
+    services::delete_credential(&services::github_credential_key(&account_id))?;
+    super::save_github_config(&conn, &config)?;
+
+    let ids: Vec<String> = config.accounts.iter().map(|a| a.id.clone()).collect();
+    database::retain_github_accounts(&conn, &ids)
+}
+
 #[tauri::command]
 pub async fn save_calendar_config(app: AppHandle, config: CalendarConfig) -> Result<()> {
-    let conn = get_connection(&app)?;
+    let mut ids = Vec::new();
+    for source in &config.sources {
+        if source.id.trim().is_empty() {
+            return Err(AppError::Validation(format!("Calendar source '{}' has no id", source.name)));
+        }
+        if ids.contains(&source.id) {
+            return Err(AppError::Validation(format!("Duplicate calendar source id: {}", source.id)));
+        }
+        ids.push(source.id.clone());
+    }
+
+    let conn = database::open(&app)?;
+
+    // Forget stored secrets of sources that were removed
+    let previous: CalendarConfig = database::get_setting(&conn, "calendar_config")?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    for removed in previous.sources.iter().filter(|s| !ids.contains(&s.id)) {
+        services::delete_credential(&services::calendar_credential_key(&removed.id))?;
+    }
+
     let config_json = serde_json::to_string(&config).unwrap_or_default();
-    database::set_setting(&conn, "calendar_config", &config_json)
+    database::set_setting(&conn, "calendar_config", &config_json)?;
+    database::retain_calendar_sources(&conn, &ids)?;
+
+    if let Err(e) = crate::watcher::sync(&app) {
+        log::warn!("Failed to update calendar file watches: {}", e);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn save_calendar_credential(source_id: String, secret: String) -> Result<()> {
+    let key = services::calendar_credential_key(&source_id);
+    if secret.is_empty() {
+        services::delete_credential(&key)
+    } else {
+        services::save_credential(&key, &secret)
+    }
+}
+
+#[tauri::command]
+pub async fn save_timezone(app: AppHandle, timezone: String) -> Result<()> {
+    if timezone.parse::<chrono_tz::Tz>().is_err() {
+        return Err(AppError::Validation(format!("Unknown timezone: {}", timezone)));
+    }
+
+    let conn = database::open(&app)?;
+    database::set_setting(&conn, "timezone", &timezone)
+}
+
+#[tauri::command]
+pub async fn save_github_max_items(app: AppHandle, max_items: usize) -> Result<()> {
+    // The search API stops at 1000 results
+    if !(1..=1000).contains(&max_items) {
+        return Err(AppError::Validation("GitHub item limit must be between 1 and 1000".to_string()));
+    }
+
+    let conn = database::open(&app)?;
+    database::set_setting(&conn, "github_max_items", &max_items.to_string())
 }
 
 #[tauri::command]
 pub async fn get_all_settings(app: AppHandle) -> Result<AllSettings> {
-    let conn = get_connection(&app)?;
+    let conn = database::open(&app)?;
 
     // Check GitHub configuration
-    let github_token = services::get_credential("github_token")?;
-    let github_configured = github_token.is_some();
+    let github_accounts = super::load_github_config(&conn)?.accounts;
+    let github_configured = !github_accounts.is_empty();
 
-    // Get GitHub username if configured
-    let github_username = if github_configured {
-        database::get_setting(&conn, "github_username")?
-    } else {
-        None
-    };
+    let github_max_items = database::get_github_max_items(&conn)?;
 
     // Get calendar config
     let calendar_config = database::get_setting(&conn, "calendar_config")?
@@ -78,6 +334,9 @@ pub async fn get_all_settings(app: AppHandle) -> Result<AllSettings> {
     // Get email config (not implemented yet)
     let email_config = EmailConfig::default();
 
+    // Effective timezone (configured or system default)
+    let timezone = database::get_user_timezone(&conn)?.name().to_string();
+
     // Get onboarding status
     let onboarding_complete = database::get_setting(&conn, "onboarding_complete")?
         .map(|v| v == "true")
@@ -85,15 +344,17 @@ pub async fn get_all_settings(app: AppHandle) -> Result<AllSettings> {
 
     Ok(AllSettings {
         github_configured,
-        github_username,
+        github_accounts,
+        github_max_items,
         calendar_config,
         email_config,
+        timezone,
         onboarding_complete,
     })
 }
 
 #[tauri::command]
 pub async fn clear_cache(app: AppHandle) -> Result<()> {
-    let conn = get_connection(&app)?;
+    let conn = database::open(&app)?;
     database::clear_all_cache(&conn)
 }
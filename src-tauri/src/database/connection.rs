@@ -0,0 +1,17 @@
+use rusqlite::Connection;
+use tauri::{AppHandle, Manager};
+use crate::error::{AppError, Result};
+use super::init_database;
+
+/// Opens the app's database in its data directory, creating and migrating it as needed.
+pub fn open(app: &AppHandle) -> Result<Connection> {
+    let app_data = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Other(format!("Failed to get app data dir: {}", e)))?;
+    std::fs::create_dir_all(&app_data)?;
+
+    let conn = Connection::open(app_data.join("wtftoday.db"))?;
+    init_database(&conn)?;
+    Ok(conn)
+}
@@ -1,5 +1,7 @@
 mod schema;
 mod repository;
+mod connection;
 
 pub use schema::*;
 pub use repository::*;
+pub use connection::*;
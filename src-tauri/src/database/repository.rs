@@ -1,6 +1,9 @@
 use rusqlite::{params, Connection};
+use chrono::Duration;
+use chrono_tz::Tz;
 use crate::error::Result;
 use crate::models::*;
+use crate::services::{is_task_overdue, start_of_day, system_timezone};
 
 // Settings operations
 pub fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>> {
@@ -22,16 +25,42 @@ pub fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<()> {
     Ok(())
 }
 
+// The user's configured IANA timezone, falling back to the system zone
+pub fn get_user_timezone(conn: &Connection) -> Result<Tz> {
+    let configured = get_setting(conn, "timezone")?.and_then(|name| name.parse().ok());
+    Ok(configured.unwrap_or_else(system_timezone))
+}
+
+// How many items each GitHub list may fetch across pages
+pub fn get_github_max_items(conn: &Connection) -> Result<usize> {
+    let configured = get_setting(conn, "github_max_items")?.and_then(|value| value.parse().ok());
+    Ok(configured.unwrap_or(DEFAULT_GITHUB_MAX_ITEMS))
+}
+
+// Drops cached GitHub data of accounts that were removed
+pub fn retain_github_accounts(conn: &Connection, account_ids: &[String]) -> Result<()> {
+    let placeholders = vec!["?"; account_ids.len()].join(", ");
+    for table in ["github_pull_requests", "github_issues", "github_notifications"] {
+        let sql = format!("DELETE FROM {} WHERE account NOT IN ({})", table, placeholders);
+        conn.execute(&sql, rusqlite::params_from_iter(account_ids))?;
+    }
+    Ok(())
+}
+
 // GitHub PR operations
-pub fn save_github_prs(conn: &Connection, prs: &[GitHubPullRequest], pr_type: &str) -> Result<()> {
+pub fn save_github_prs(conn: &Connection, account: &str, prs: &[GitHubPullRequest], pr_type: &str) -> Result<()> {
     // Clear old PRs of this type
-    conn.execute("DELETE FROM github_pull_requests WHERE pr_type = ?", params![pr_type])?;
+    conn.execute(
+        "DELETE FROM github_pull_requests WHERE account = ? AND pr_type = ?",
+        params![account, pr_type],
+    )?;
 
     let mut stmt = conn.prepare(
         r#"INSERT OR REPLACE INTO github_pull_requests
            (id, repo_full_name, number, title, state, draft, user_login, user_avatar_url,
-            html_url, created_at, updated_at, requested_reviewers, labels, review_status, pr_type, cached_at)
-           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, strftime('%s', 'now'))"#
+            html_url, created_at, updated_at, requested_reviewers, labels, review_status, ci_status, mergeable,
+            pr_type, cached_at, account)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, strftime('%s', 'now'), ?)"#
     )?;
 
     for pr in prs {
@@ -50,7 +79,10 @@ pub fn save_github_prs(conn: &Connection, prs: &[GitHubPullRequest], pr_type: &s
             serde_json::to_string(&pr.requested_reviewers).unwrap_or_default(),
             serde_json::to_string(&pr.labels).unwrap_or_default(),
             pr.review_status,
+            pr.ci_status,
+            pr.mergeable,
             pr_type,
+            account,
         ])?;
     }
 
@@ -60,7 +92,8 @@ pub fn save_github_prs(conn: &Connection, prs: &[GitHubPullRequest], pr_type: &s
 pub fn get_github_prs(conn: &Connection, pr_type: &str) -> Result<Vec<GitHubPullRequest>> {
     let mut stmt = conn.prepare(
         r#"SELECT id, repo_full_name, number, title, state, draft, user_login, user_avatar_url,
-                  html_url, created_at, updated_at, requested_reviewers, labels, review_status
+                  html_url, created_at, updated_at, requested_reviewers, labels, review_status, ci_status, mergeable,
+                  account
            FROM github_pull_requests WHERE pr_type = ? ORDER BY updated_at DESC"#
     )?;
 
@@ -70,6 +103,7 @@ pub fn get_github_prs(conn: &Connection, pr_type: &str) -> Result<Vec<GitHubPull
 
         Ok(GitHubPullRequest {
             id: row.get(0)?,
+            account: row.get(16)?,
             repo_full_name: row.get(1)?,
             number: row.get(2)?,
             title: row.get(3)?,
@@ -83,6 +117,8 @@ pub fn get_github_prs(conn: &Connection, pr_type: &str) -> Result<Vec<GitHubPull
             requested_reviewers: serde_json::from_str(&reviewers_json).unwrap_or_default(),
             labels: serde_json::from_str(&labels_json).unwrap_or_default(),
             review_status: row.get(13)?,
+            ci_status: row.get(14)?,
+            mergeable: row.get(15)?,
         })
     })?.collect::<std::result::Result<Vec<_>, _>>()?;
 
@@ -90,14 +126,14 @@ pub fn get_github_prs(conn: &Connection, pr_type: &str) -> Result<Vec<GitHubPull
 }
 
 // GitHub Issues operations
-pub fn save_github_issues(conn: &Connection, issues: &[GitHubIssue]) -> Result<()> {
-    conn.execute("DELETE FROM github_issues", [])?;
+pub fn save_github_issues(conn: &Connection, account: &str, issues: &[GitHubIssue]) -> Result<()> {
+    conn.execute("DELETE FROM github_issues WHERE account = ?", params![account])?;
 
     let mut stmt = conn.prepare(
         r#"INSERT OR REPLACE INTO github_issues
            (id, repo_full_name, number, title, state, user_login, user_avatar_url,
-            html_url, body_preview, labels, cached_at)
-           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, strftime('%s', 'now'))"#
+            html_url, body_preview, labels, cached_at, account)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, strftime('%s', 'now'), ?)"#
     )?;
 
     for issue in issues {
@@ -112,6 +148,7 @@ pub fn save_github_issues(conn: &Connection, issues: &[GitHubIssue]) -> Result<(
             issue.html_url,
             issue.body_preview,
             serde_json::to_string(&issue.labels).unwrap_or_default(),
+            account,
         ])?;
     }
 
@@ -120,7 +157,7 @@ pub fn save_github_issues(conn: &Connection, issues: &[GitHubIssue]) -> Result<(
 
 pub fn get_github_issues(conn: &Connection) -> Result<Vec<GitHubIssue>> {
     let mut stmt = conn.prepare(
-        r#"SELECT id, repo_full_name, number, title, state, user_login, html_url, body_preview, labels
+        r#"SELECT id, repo_full_name, number, title, state, user_login, html_url, body_preview, labels, account
            FROM github_issues ORDER BY id DESC"#
     )?;
 
@@ -129,6 +166,7 @@ pub fn get_github_issues(conn: &Connection) -> Result<Vec<GitHubIssue>> {
 
         Ok(GitHubIssue {
             id: row.get(0)?,
+            account: row.get(9)?,
             repo_full_name: row.get(1)?,
             number: row.get(2)?,
             title: row.get(3)?,
@@ -144,13 +182,13 @@ pub fn get_github_issues(conn: &Connection) -> Result<Vec<GitHubIssue>> {
 }
 
 // GitHub Notifications operations
-pub fn save_github_notifications(conn: &Connection, notifications: &[GitHubNotification]) -> Result<()> {
-    conn.execute("DELETE FROM github_notifications", [])?;
+pub fn save_github_notifications(conn: &Connection, account: &str, notifications: &[GitHubNotification]) -> Result<()> {
+    conn.execute("DELETE FROM github_notifications WHERE account = ?", params![account])?;
 
     let mut stmt = conn.prepare(
         r#"INSERT OR REPLACE INTO github_notifications
-           (id, repo_full_name, subject_title, subject_type, subject_url, reason, unread, updated_at, cached_at)
-           VALUES (?, ?, ?, ?, ?, ?, ?, ?, strftime('%s', 'now'))"#
+           (id, repo_full_name, subject_title, subject_type, subject_url, reason, unread, updated_at, cached_at, account)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, strftime('%s', 'now'), ?)"#
     )?;
 
     for notif in notifications {
@@ -163,6 +201,7 @@ pub fn save_github_notifications(conn: &Connection, notifications: &[GitHubNotif
             notif.reason,
             notif.unread,
             notif.updated_at,
+            account,
         ])?;
     }
 
@@ -171,13 +210,14 @@ pub fn save_github_notifications(conn: &Connection, notifications: &[GitHubNotif
 
 pub fn get_github_notifications(conn: &Connection) -> Result<Vec<GitHubNotification>> {
     let mut stmt = conn.prepare(
-        r#"SELECT id, repo_full_name, subject_title, subject_type, subject_url, reason, unread, updated_at
+        r#"SELECT id, repo_full_name, subject_title, subject_type, subject_url, reason, unread, updated_at, account
            FROM github_notifications ORDER BY updated_at DESC"#
     )?;
 
     let notifications = stmt.query_map([], |row| {
         Ok(GitHubNotification {
             id: row.get(0)?,
+            account: row.get(8)?,
             repo_full_name: row.get(1)?,
             subject_title: row.get(2)?,
             subject_type: row.get(3)?,
@@ -191,14 +231,39 @@ pub fn get_github_notifications(conn: &Connection) -> Result<Vec<GitHubNotificat
     Ok(notifications)
 }
 
+pub fn mark_github_notification_read(conn: &Connection, account: &str, id: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE github_notifications SET unread = 0 WHERE account = ? AND id = ?",
+        params![account, id],
+    )?;
+    Ok(())
+}
+
+pub fn mark_github_repo_notifications_read(conn: &Connection, account: &str, repo_full_name: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE github_notifications SET unread = 0 WHERE account = ? AND repo_full_name = ?",
+        params![account, repo_full_name],
+    )?;
+    Ok(())
+}
+
+pub fn delete_github_notification(conn: &Connection, account: &str, id: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM github_notifications WHERE account = ? AND id = ?",
+        params![account, id],
+    )?;
+    Ok(())
+}
+
 // Calendar operations
 pub fn save_calendar_events(conn: &Connection, events: &[CalendarEvent], source: &str) -> Result<()> {
     conn.execute("DELETE FROM calendar_events WHERE source = ?", params![source])?;
 
     let mut stmt = conn.prepare(
         r#"INSERT OR REPLACE INTO calendar_events
-           (id, source, summary, description, location, start_time, end_time, all_day, html_link, cached_at)
-           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, strftime('%s', 'now'))"#
+           (id, source, summary, description, location, start_time, end_time, all_day, html_link,
+            join_url, conference_provider, organizer, attendees, out_of_office, reminder_offsets, cached_at)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, strftime('%s', 'now'))"#
     )?;
 
     for event in events {
@@ -212,62 +277,215 @@ pub fn save_calendar_events(conn: &Connection, events: &[CalendarEvent], source:
             event.end_time,
             event.all_day,
             event.html_link,
+            event.join_url,
+            event.conference_provider,
+            event.organizer.as_ref().and_then(|o| serde_json::to_string(o).ok()),
+            serde_json::to_string(&event.attendees).unwrap_or_default(),
+            event.out_of_office,
+            serde_json::to_string(&event.reminder_offsets).unwrap_or_default(),
         ])?;
     }
 
     Ok(())
 }
 
-pub fn get_calendar_events_for_today(conn: &Connection) -> Result<Vec<CalendarEvent>> {
-    let now = chrono::Utc::now();
-    let today_start = now.date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp();
-    let today_end = now.date_naive().and_hms_opt(23, 59, 59).unwrap().and_utc().timestamp();
-    let now_ts = now.timestamp();
-    let soon_ts = now_ts + 1800; // 30 minutes from now
+// Drops cached events and tasks belonging to sources that are no longer configured
+pub fn retain_calendar_sources(conn: &Connection, source_ids: &[String]) -> Result<()> {
+    let placeholders = vec!["?"; source_ids.len()].join(", ");
+    for table in ["calendar_events", "calendar_tasks"] {
+        let sql = format!("DELETE FROM {} WHERE source NOT IN ({})", table, placeholders);
+        conn.execute(&sql, rusqlite::params_from_iter(source_ids))?;
+    }
+    Ok(())
+}
+
+pub fn save_calendar_tasks(conn: &Connection, tasks: &[CalendarTask], source: &str) -> Result<()> {
+    conn.execute("DELETE FROM calendar_tasks WHERE source = ?", params![source])?;
 
     let mut stmt = conn.prepare(
-        r#"SELECT id, summary, description, location, start_time, end_time, all_day, html_link
-           FROM calendar_events
-           WHERE (start_time >= ? AND start_time <= ?) OR (start_time <= ? AND end_time >= ?)
-           ORDER BY all_day DESC, start_time ASC"#
+        r#"INSERT OR REPLACE INTO calendar_tasks
+           (id, source, summary, description, due_time, due_all_day, priority, status, percent_complete, cached_at)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, strftime('%s', 'now'))"#
     )?;
 
-    let events = stmt.query_map(params![today_start, today_end, now_ts, today_start], |row| {
-        let start_time: i64 = row.get(4)?;
-        let end_time: i64 = row.get(5)?;
-        let is_now = start_time <= now_ts && end_time > now_ts;
-        let is_soon = !is_now && start_time > now_ts && start_time <= soon_ts;
+    for task in tasks {
+        stmt.execute(params![
+            task.id,
+            source,
+            task.summary,
+            task.description,
+            task.due_time,
+            task.due_all_day,
+            task.priority,
+            task.status,
+            task.percent_complete,
+        ])?;
+    }
 
-        Ok(CalendarEvent {
+    Ok(())
+}
+
+// Open tasks due today or earlier, most urgent first
+pub fn get_due_calendar_tasks(conn: &Connection, tz: Tz) -> Result<Vec<CalendarTask>> {
+    let today = chrono::Utc::now().with_timezone(&tz).date_naive();
+    get_calendar_tasks_due_between(conn, tz, i64::MIN, start_of_day(tz, today + Duration::days(1)))
+}
+
+// Open tasks due in [start, end), most urgent first
+pub fn get_calendar_tasks_due_between(conn: &Connection, tz: Tz, start: i64, end: i64) -> Result<Vec<CalendarTask>> {
+    let today = chrono::Utc::now().with_timezone(&tz).date_naive();
+    let today_start = start_of_day(tz, today);
+
+    let mut stmt = conn.prepare(
+        r#"SELECT id, source, summary, description, due_time, due_all_day, priority, status, percent_complete
+           FROM calendar_tasks
+           WHERE due_time IS NOT NULL AND due_time >= ? AND due_time < ?
+           ORDER BY due_time ASC, priority IS NULL, priority ASC"#
+    )?;
+
+    let now_ts = chrono::Utc::now().timestamp();
+    let tasks = stmt.query_map(params![start, end], |row| {
+        let due_time: Option<i64> = row.get(4)?;
+        let due_all_day: bool = row.get(5)?;
+
+        Ok(CalendarTask {
             id: row.get(0)?,
-            summary: row.get(1)?,
-            description: row.get(2)?,
-            location: row.get(3)?,
-            start_time,
-            end_time,
-            all_day: row.get(6)?,
-            html_link: row.get(7)?,
-            is_now,
-            is_soon,
+            source: row.get(1)?,
+            summary: row.get(2)?,
+            description: row.get(3)?,
+            due_time,
+            due_all_day,
+            priority: row.get(6)?,
+            status: row.get(7)?,
+            percent_complete: row.get(8)?,
+            color: None, // Filled from the source config by the caller
+            is_overdue: is_task_overdue(due_time, due_all_day, now_ts, today_start),
         })
     })?.collect::<std::result::Result<Vec<_>, _>>()?;
 
+    Ok(tasks)
+}
+
+// Anything overlapping [start, end), e.g. the user's local day
+pub fn get_calendar_events_between(conn: &Connection, start: i64, end: i64) -> Result<Vec<CalendarEvent>> {
+    let mut stmt = conn.prepare(
+        r#"SELECT id, source, summary, description, location, start_time, end_time, all_day, html_link,
+                  join_url, conference_provider, organizer, attendees, out_of_office, reminder_offsets
+           FROM calendar_events
+           WHERE start_time < ? AND end_time > ?
+           ORDER BY all_day DESC, start_time ASC"#
+    )?;
+
+    let now_ts = chrono::Utc::now().timestamp();
+    let events = stmt
+        .query_map(params![end, start], |row| calendar_event_from_row(row, now_ts))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(events)
+}
+
+pub fn get_calendar_events_by_source(conn: &Connection, source: &str) -> Result<Vec<CalendarEvent>> {
+    let mut stmt = conn.prepare(
+        r#"SELECT id, source, summary, description, location, start_time, end_time, all_day, html_link,
+                  join_url, conference_provider, organizer, attendees, out_of_office, reminder_offsets
+           FROM calendar_events
+           WHERE source = ?
+           ORDER BY all_day DESC, start_time ASC"#
+    )?;
+
+    let now_ts = chrono::Utc::now().timestamp();
+    let events = stmt
+        .query_map(params![source], |row| calendar_event_from_row(row, now_ts))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(events)
+}
+
+pub fn get_calendar_events_starting_between(conn: &Connection, start: i64, end: i64) -> Result<Vec<CalendarEvent>> {
+    let mut stmt = conn.prepare(
+        r#"SELECT id, source, summary, description, location, start_time, end_time, all_day, html_link,
+                  join_url, conference_provider, organizer, attendees, out_of_office, reminder_offsets
+           FROM calendar_events
+           WHERE start_time >= ? AND start_time < ?
+           ORDER BY start_time ASC"#
+    )?;
+
+    let now_ts = chrono::Utc::now().timestamp();
+    let events = stmt
+        .query_map(params![start, end], |row| calendar_event_from_row(row, now_ts))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
     Ok(events)
 }
 
+/// How long after its start the latest cached VALARM fires, in seconds. Alarms that fire
+/// after the start are stored as negative lead times.
+pub fn get_max_reminder_delay(conn: &Connection) -> Result<i64> {
+    let delay: Option<i64> = conn.query_row(
+        "SELECT MAX(-offset.value) FROM calendar_events, json_each(calendar_events.reminder_offsets) AS offset",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(delay.unwrap_or(0).max(0))
+}
+
+fn calendar_event_from_row(row: &rusqlite::Row, now_ts: i64) -> rusqlite::Result<CalendarEvent> {
+    let soon_ts = now_ts + 1800; // 30 minutes from now
+    let start_time: i64 = row.get(5)?;
+    let end_time: i64 = row.get(6)?;
+    let is_now = start_time <= now_ts && end_time > now_ts;
+    let is_soon = !is_now && start_time > now_ts && start_time <= soon_ts;
+    let organizer_json: Option<String> = row.get(11)?;
+    let attendees_json: Option<String> = row.get(12)?;
+    let reminders_json: Option<String> = row.get(14)?;
+
+    Ok(CalendarEvent {
+        id: row.get(0)?,
+        source: row.get(1)?,
+        summary: row.get(2)?,
+        description: row.get(3)?,
+        location: row.get(4)?,
+        start_time,
+        end_time,
+        all_day: row.get(7)?,
+        html_link: row.get(8)?,
+        join_url: row.get(9)?,
+        conference_provider: row.get(10)?,
+        organizer: organizer_json.and_then(|json| serde_json::from_str(&json).ok()),
+        attendees: attendees_json
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        my_status: None, // Derived from the configured addresses by the caller
+        out_of_office: row.get(13)?,
+        reminder_offsets: reminders_json
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        color: None, // Filled from the source config by the caller
+        is_now,
+        is_soon,
+    })
+}
+
 // Cache metadata operations
-pub fn set_cache_metadata(conn: &Connection, source: &str, etag: Option<&str>) -> Result<()> {
+pub fn set_cache_metadata(conn: &Connection, source: &str, validators: &CacheValidators) -> Result<()> {
     conn.execute(
-        "INSERT OR REPLACE INTO cache_metadata (source, last_fetch, etag) VALUES (?, strftime('%s', 'now'), ?)",
-        params![source, etag],
+        "INSERT OR REPLACE INTO cache_metadata (source, last_fetch, etag, last_modified, poll_interval) VALUES (?, strftime('%s', 'now'), ?, ?, ?)",
+        params![source, validators.etag, validators.last_modified, validators.poll_interval],
     )?;
     Ok(())
 }
 
-pub fn get_cache_metadata(conn: &Connection, source: &str) -> Result<Option<(i64, Option<String>)>> {
-    let mut stmt = conn.prepare("SELECT last_fetch, etag FROM cache_metadata WHERE source = ?")?;
+pub fn get_cache_metadata(conn: &Connection, source: &str) -> Result<Option<(i64, CacheValidators)>> {
+    let mut stmt = conn.prepare("SELECT last_fetch, etag, last_modified, poll_interval FROM cache_metadata WHERE source = ?")?;
     let result = stmt.query_row(params![source], |row| {
-        Ok((row.get(0)?, row.get(1)?))
+        Ok((
+            row.get(0)?,
+            CacheValidators {
+                etag: row.get(1)?,
+                last_modified: row.get(2)?,
+                poll_interval: row.get(3)?,
+            },
+        ))
     });
 
     match result {
@@ -285,6 +503,7 @@ pub fn clear_all_cache(conn: &Connection) -> Result<()> {
         DELETE FROM github_issues;
         DELETE FROM github_notifications;
         DELETE FROM calendar_events;
+        DELETE FROM calendar_tasks;
         DELETE FROM cache_metadata;
         "#
     )?;
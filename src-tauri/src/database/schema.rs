@@ -2,6 +2,9 @@ use rusqlite::Connection;
 use crate::error::Result;
 
 pub fn init_database(conn: &Connection) -> Result<()> {
+    drop_unscoped_github_cache(conn)?;
+    drop_unscoped_calendar_cache(conn)?;
+
     conn.execute_batch(
         r#"
         -- Settings table for non-sensitive configuration
@@ -13,7 +16,7 @@ pub fn init_database(conn: &Connection) -> Result<()> {
 
         -- GitHub Pull Requests cache
         CREATE TABLE IF NOT EXISTS github_pull_requests (
-            id INTEGER PRIMARY KEY,
+            id INTEGER NOT NULL,
             repo_full_name TEXT NOT NULL,
             number INTEGER NOT NULL,
             title TEXT NOT NULL,
@@ -27,14 +30,18 @@ pub fn init_database(conn: &Connection) -> Result<()> {
             requested_reviewers TEXT,
             labels TEXT,
             review_status TEXT,
+            ci_status TEXT,
+            mergeable TEXT,
             pr_type TEXT NOT NULL DEFAULT 'other',
             cached_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
-            UNIQUE(repo_full_name, number)
+            account TEXT NOT NULL DEFAULT '',
+            PRIMARY KEY(account, id),
+            UNIQUE(account, repo_full_name, number)
         );
 
         -- GitHub Issues cache
         CREATE TABLE IF NOT EXISTS github_issues (
-            id INTEGER PRIMARY KEY,
+            id INTEGER NOT NULL,
             repo_full_name TEXT NOT NULL,
             number INTEGER NOT NULL,
             title TEXT NOT NULL,
@@ -45,12 +52,14 @@ pub fn init_database(conn: &Connection) -> Result<()> {
             body_preview TEXT,
             labels TEXT,
             cached_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
-            UNIQUE(repo_full_name, number)
+            account TEXT NOT NULL DEFAULT '',
+            PRIMARY KEY(account, id),
+            UNIQUE(account, repo_full_name, number)
         );
 
         -- GitHub Notifications cache
         CREATE TABLE IF NOT EXISTS github_notifications (
-            id TEXT PRIMARY KEY,
+            id TEXT NOT NULL,
             repo_full_name TEXT NOT NULL,
             subject_title TEXT NOT NULL,
             subject_type TEXT NOT NULL,
@@ -58,12 +67,14 @@ pub fn init_database(conn: &Connection) -> Result<()> {
             reason TEXT NOT NULL,
             unread INTEGER NOT NULL DEFAULT 1,
             updated_at TEXT NOT NULL,
-            cached_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+            cached_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+            account TEXT NOT NULL DEFAULT '',
+            PRIMARY KEY(account, id)
         );
 
         -- Calendar Events cache
         CREATE TABLE IF NOT EXISTS calendar_events (
-            id TEXT PRIMARY KEY,
+            id TEXT NOT NULL,
             source TEXT NOT NULL,
             summary TEXT NOT NULL,
             description TEXT,
@@ -72,14 +83,38 @@ pub fn init_database(conn: &Connection) -> Result<()> {
             end_time INTEGER NOT NULL,
             all_day INTEGER NOT NULL DEFAULT 0,
             html_link TEXT,
-            cached_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+            join_url TEXT,
+            conference_provider TEXT,
+            organizer TEXT,
+            attendees TEXT,
+            out_of_office INTEGER NOT NULL DEFAULT 0,
+            reminder_offsets TEXT,
+            cached_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+            PRIMARY KEY(source, id)
+        );
+
+        -- Calendar Tasks (VTODO) cache
+        CREATE TABLE IF NOT EXISTS calendar_tasks (
+            id TEXT NOT NULL,
+            source TEXT NOT NULL,
+            summary TEXT NOT NULL,
+            description TEXT,
+            due_time INTEGER,
+            due_all_day INTEGER NOT NULL DEFAULT 0,
+            priority INTEGER,
+            status TEXT NOT NULL,
+            percent_complete INTEGER,
+            cached_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+            PRIMARY KEY(source, id)
         );
 
         -- Cache metadata for invalidation
         CREATE TABLE IF NOT EXISTS cache_metadata (
             source TEXT PRIMARY KEY,
             last_fetch INTEGER NOT NULL,
-            etag TEXT
+            etag TEXT,
+            last_modified TEXT,
+            poll_interval INTEGER
         );
 
         -- Create indexes
@@ -87,8 +122,89 @@ pub fn init_database(conn: &Connection) -> Result<()> {
         CREATE INDEX IF NOT EXISTS idx_github_prs_type ON github_pull_requests(pr_type);
         CREATE INDEX IF NOT EXISTS idx_github_issues_cached ON github_issues(cached_at);
         CREATE INDEX IF NOT EXISTS idx_calendar_events_start ON calendar_events(start_time);
+        CREATE INDEX IF NOT EXISTS idx_calendar_tasks_due ON calendar_tasks(due_time);
         "#,
     )?;
 
+    migrate(conn)?;
+
+    Ok(())
+}
+
+// Columns added after the first release; CREATE TABLE IF NOT EXISTS leaves older databases as they were
+fn migrate(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "cache_metadata", "last_modified", "TEXT")?;
+    add_column_if_missing(conn, "calendar_events", "join_url", "TEXT")?;
+    add_column_if_missing(conn, "calendar_events", "conference_provider", "TEXT")?;
+    add_column_if_missing(conn, "calendar_events", "organizer", "TEXT")?;
+    add_column_if_missing(conn, "calendar_events", "attendees", "TEXT")?;
+    add_column_if_missing(conn, "calendar_events", "out_of_office", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "calendar_events", "reminder_offsets", "TEXT")?;
+    add_column_if_missing(conn, "github_pull_requests", "ci_status", "TEXT")?;
+    add_column_if_missing(conn, "github_pull_requests", "mergeable", "TEXT")?;
+    add_column_if_missing(conn, "cache_metadata", "poll_interval", "INTEGER")?;
+    Ok(())
+}
+
+// GitHub caches from before multiple accounts were keyed by item id alone, which ALTER TABLE
+// can't change. They only hold cached data, so drop them and let init_database recreate them.
+fn drop_unscoped_github_cache(conn: &Connection) -> Result<()> {
+    for table in ["github_pull_requests", "github_issues", "github_notifications"] {
+        if table_exists(conn, table)? && !column_exists(conn, table, "account")? {
+            conn.execute(&format!("DROP TABLE {}", table), [])?;
+        }
+    }
+    Ok(())
+}
+
+// Calendar caches used to be keyed by UID alone, so a meeting shared by two sources had one
+// row that either source could overwrite or delete. Dropping them also forgets the sources'
+// validators, since a 304 would otherwise leave a source with nothing cached.
+fn drop_unscoped_calendar_cache(conn: &Connection) -> Result<()> {
+    let mut dropped = false;
+    for table in ["calendar_events", "calendar_tasks"] {
+        if table_exists(conn, table)? && !primary_key_includes(conn, table, "source")? {
+            conn.execute(&format!("DROP TABLE {}", table), [])?;
+            dropped = true;
+        }
+    }
+    if dropped && table_exists(conn, "cache_metadata")? {
+        conn.execute("DELETE FROM cache_metadata WHERE source LIKE 'calendar:%'", [])?;
+    }
+    Ok(())
+}
+
+fn table_exists(conn: &Connection, table: &str) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?",
+        [table],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+fn column_exists(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|name| name.ok())
+        .any(|name| name == column);
+    Ok(exists)
+}
+
+fn primary_key_includes(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let included = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(1)?, row.get::<_, i64>(5)?)))?
+        .filter_map(|info| info.ok())
+        .any(|(name, pk)| name == column && pk > 0);
+    Ok(included)
+}
+
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    if !column_exists(conn, table, column)? {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+
     Ok(())
 }
@@ -24,6 +24,11 @@ pub enum AppError {
     #[error("Parse error: {0}")]
     Parse(String),
 
+    // Unix time at which the exhausted budget resets; commands say when that is in the
+    // user's timezone
+    #[error("GitHub rate limit exceeded")]
+    RateLimited(i64),
+
     #[error("{0}")]
     Other(String),
 }
@@ -3,6 +3,8 @@ mod models;
 mod database;
 mod services;
 mod commands;
+mod scheduler;
+mod watcher;
 
 use commands::*;
 
@@ -10,6 +12,12 @@ use commands::*;
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
+        .setup(|app| {
+            scheduler::start(app.handle().clone());
+            watcher::start(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // Settings commands
             is_onboarding_complete,
@@ -18,14 +26,28 @@ pub fn run() {
             save_github_token,
             validate_github_token,
             save_calendar_config,
+            save_calendar_credential,
+            save_timezone,
+            save_github_max_items,
+            add_github_account,
+            remove_github_account,
+            start_github_device_flow,
+            poll_github_device_flow,
             get_all_settings,
             clear_cache,
             // GitHub commands
             fetch_github_data,
             get_cached_github_data,
+            get_github_rate_limits,
+            mark_github_notification_read,
+            mark_github_notification_done,
+            mark_github_repo_notifications_read,
+            unsubscribe_github_notification,
             // Calendar commands
             fetch_calendar_events,
             get_cached_calendar_events,
+            get_due_calendar_tasks,
+            get_calendar_events_in_range,
             parse_ics_file,
             // Brief commands
             refresh_brief,
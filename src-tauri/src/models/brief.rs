@@ -1,10 +1,15 @@
 use serde::{Deserialize, Serialize};
-use super::{GitHubBriefData, CalendarEvent, EmailHeader};
+use super::{GitHubBriefData, CalendarConflict, CalendarEvent, CalendarTask, DaySchedule, EmailHeader, Lookahead};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BriefData {
     pub github: Option<GitHubBriefData>,
     pub calendar: Vec<CalendarEvent>,
+    pub pending_invitations: Vec<CalendarEvent>,
+    pub schedule: DaySchedule,
+    pub conflicts: Vec<CalendarConflict>,
+    pub tasks: Vec<CalendarTask>,
+    pub lookahead: Lookahead,
     pub email: Vec<EmailHeader>,
     pub generated_at: i64,
 }
@@ -14,6 +19,11 @@ impl Default for BriefData {
         Self {
             github: None,
             calendar: Vec::new(),
+            pending_invitations: Vec::new(),
+            schedule: DaySchedule::default(),
+            conflicts: Vec::new(),
+            tasks: Vec::new(),
+            lookahead: Lookahead::default(),
             email: Vec::new(),
             generated_at: chrono::Utc::now().timestamp(),
         }
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+// HTTP validators from the last successful fetch, replayed as If-None-Match/If-Modified-Since
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheValidators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    // Seconds the server asks clients to wait between polls (GitHub's X-Poll-Interval)
+    #[serde(default)]
+    pub poll_interval: Option<i64>,
+}
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CalendarEvent {
     pub id: String,
+    pub source: String,
     pub summary: String,
     pub description: Option<String>,
     pub location: Option<String>,
@@ -10,23 +11,177 @@ pub struct CalendarEvent {
     pub end_time: i64,
     pub all_day: bool,
     pub html_link: Option<String>,
+    pub join_url: Option<String>,
+    pub conference_provider: Option<String>, // "zoom", "google_meet", "teams", "jitsi", "webex"
+    pub organizer: Option<EventAttendee>,
+    pub attendees: Vec<EventAttendee>,
+    pub my_status: Option<String>, // Participation status of the user, if they are invited
+    pub out_of_office: bool,
+    pub reminder_offsets: Vec<i64>, // Seconds before the start at which the feed's VALARMs fire
+    pub color: Option<String>,
     pub is_now: bool,
     pub is_soon: bool,
 }
 
+impl CalendarEvent {
+    pub fn is_declined(&self) -> bool {
+        self.my_status.as_deref() == Some("declined")
+    }
+
+    pub fn needs_response(&self) -> bool {
+        self.my_status.as_deref() == Some("needs_action")
+    }
+}
+
+// A VTODO; only open tasks are kept
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CalendarConfig {
-    pub source_type: String, // "ics_file", "ics_url", "none"
-    pub ics_path: Option<String>,
+pub struct CalendarTask {
+    pub id: String,
+    pub source: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub due_time: Option<i64>,
+    pub due_all_day: bool,
+    pub priority: Option<u8>, // 1 (highest) to 9
+    pub status: String,       // "needs_action", "in_process"
+    pub percent_complete: Option<u8>,
+    pub color: Option<String>,
+    pub is_overdue: bool,
+}
+
+// Payload of the "meeting-reminder" event sent to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingReminder {
+    pub event: CalendarEvent,
+    pub remind_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventAttendee {
+    pub email: String,
+    pub name: Option<String>,
+    pub role: Option<String>,   // "chair", "req_participant", "opt_participant", "non_participant"
+    pub status: Option<String>, // "needs_action", "accepted", "declined", "tentative", "delegated"
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarSource {
+    pub id: String,
+    pub name: String,
+    pub source_type: String, // "ics_file", "ics_url", "caldav", "vdir"
+    pub ics_path: Option<String>, // File for "ics_file", directory for "vdir"
     pub ics_url: Option<String>,
+    #[serde(default)]
+    pub caldav_url: Option<String>,
+    #[serde(default)]
+    pub username: Option<String>, // CalDAV or feed basic-auth login; the secret lives in the keyring
+    pub color: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_refresh_interval")]
+    pub refresh_interval_minutes: u32,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_refresh_interval() -> u32 {
+    5
+}
+
+fn default_reminder_minutes() -> u32 {
+    5
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "StoredCalendarConfig")]
+pub struct CalendarConfig {
+    pub sources: Vec<CalendarSource>,
+    // Addresses the user is invited under, used to find their RSVP on each event
+    pub my_emails: Vec<String>,
+    pub hide_declined: bool,
+    pub reminders_enabled: bool,
+    // Lead time for events that carry no VALARM of their own
+    pub default_reminder_minutes: u32,
 }
 
 impl Default for CalendarConfig {
     fn default() -> Self {
         Self {
-            source_type: "none".to_string(),
-            ics_path: None,
-            ics_url: None,
+            sources: Vec::new(),
+            my_emails: Vec::new(),
+            hide_declined: false,
+            reminders_enabled: default_enabled(),
+            default_reminder_minutes: default_reminder_minutes(),
+        }
+    }
+}
+
+impl CalendarConfig {
+    pub fn enabled_sources(&self) -> impl Iterator<Item = &CalendarSource> {
+        self.sources.iter().filter(|s| s.enabled)
+    }
+
+    pub fn source(&self, id: &str) -> Option<&CalendarSource> {
+        self.sources.iter().find(|s| s.id == id)
+    }
+}
+
+// Accepts both the current list format and the single-source format saved by earlier versions
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredCalendarConfig {
+    Sources {
+        sources: Vec<CalendarSource>,
+        #[serde(default)]
+        my_emails: Vec<String>,
+        #[serde(default)]
+        hide_declined: bool,
+        #[serde(default = "default_enabled")]
+        reminders_enabled: bool,
+        #[serde(default = "default_reminder_minutes")]
+        default_reminder_minutes: u32,
+    },
+    Legacy {
+        source_type: String,
+        ics_path: Option<String>,
+        ics_url: Option<String>,
+    },
+}
+
+impl From<StoredCalendarConfig> for CalendarConfig {
+    fn from(stored: StoredCalendarConfig) -> Self {
+        match stored {
+            StoredCalendarConfig::Sources {
+                sources,
+                my_emails,
+                hide_declined,
+                reminders_enabled,
+                default_reminder_minutes,
+            } => Self {
+                sources,
+                my_emails,
+                hide_declined,
+                reminders_enabled,
+                default_reminder_minutes,
+            },
+            StoredCalendarConfig::Legacy { source_type, .. } if source_type == "none" => Self::default(),
+            StoredCalendarConfig::Legacy { source_type, ics_path, ics_url } => Self {
+                sources: vec![CalendarSource {
+                    id: "default".to_string(),
+                    name: "Calendar".to_string(),
+                    source_type,
+                    ics_path,
+                    ics_url,
+                    caldav_url: None,
+                    username: None,
+                    color: None,
+                    enabled: true,
+                    refresh_interval_minutes: default_refresh_interval(),
+                }],
+                ..Self::default()
+            },
         }
     }
 }
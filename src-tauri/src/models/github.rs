@@ -16,6 +16,7 @@ pub struct GitHubLabel {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GitHubPullRequest {
     pub id: i64,
+    pub account: String, // id of the GitHubAccount it was fetched with
     pub repo_full_name: String,
     pub number: i32,
     pub title: String,
@@ -28,12 +29,15 @@ pub struct GitHubPullRequest {
     pub updated_at: String,
     pub requested_reviewers: Vec<String>,
     pub labels: Vec<GitHubLabel>,
-    pub review_status: Option<String>,
+    pub review_status: Option<String>, // "approved", "changes_requested", "pending"
+    pub ci_status: Option<String>,     // "success", "failure", "pending"; None without checks
+    pub mergeable: Option<String>,     // "mergeable", "conflicting", "unknown"
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GitHubIssue {
     pub id: i64,
+    pub account: String,
     pub repo_full_name: String,
     pub number: i32,
     pub title: String,
@@ -47,6 +51,7 @@ pub struct GitHubIssue {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GitHubNotification {
     pub id: String,
+    pub account: String,
     pub repo_full_name: String,
     pub subject_title: String,
     pub subject_type: String,
@@ -56,13 +61,178 @@ pub struct GitHubNotification {
     pub url: Option<String>,
 }
 
+// Items fetched per list unless the user configured another cap
+pub const DEFAULT_GITHUB_MAX_ITEMS: usize = 100;
+
+pub const GITHUB_DOT_COM_API_URL: &str = "https://api.github.com";
+
+// Client ID of the OAuth app (not a GitHub App, whose tokens can't read notifications) used to
+// sign in to github.com, taken from GITHUB_OAUTH_CLIENT_ID at build time. The device flow needs
+// no secret. Without one, and on Enterprise Server, users enter the ID of an app they registered.
+pub const GITHUB_OAUTH_CLIENT_ID: Option<&str> = option_env!("GITHUB_OAUTH_CLIENT_ID");
+
+// Scopes asked for when signing in, the same ones a personal access token needs
+pub const GITHUB_OAUTH_SCOPES: &str = "repo notifications read:user";
+
+// Where an account's API lives: github.com, or a GitHub Enterprise Server such as
+// https://ghe.example.com/api/v3 with GraphQL at https://ghe.example.com/api/graphql
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitHubEndpoints {
+    pub api_url: String,
+    pub graphql_url: String,
+}
+
+impl Default for GitHubEndpoints {
+    fn default() -> Self {
+        Self::for_api_url(GITHUB_DOT_COM_API_URL)
+    }
+}
+
+impl GitHubEndpoints {
+    /// Endpoints for a REST API base, with GraphQL where GitHub serves it for that base.
+    pub fn for_api_url(api_url: &str) -> Self {
+        let api_url = api_url.trim().trim_end_matches('/').to_string();
+        let graphql_url = match api_url.strip_suffix("/api/v3") {
+            Some(server) => format!("{}/api/graphql", server),
+            None => format!("{}/graphql", api_url),
+        };
+        Self { api_url, graphql_url }
+    }
+
+    /// Base of the web UI: https://github.com for api.github.com, the server itself for GHES.
+    pub fn web_url(&self) -> String {
+        if let Some(server) = self.api_url.strip_suffix("/api/v3") {
+            return server.to_string();
+        }
+        match self.api_url.split_once("://") {
+            Some((scheme, host)) => format!("{}://{}", scheme, host.strip_prefix("api.").unwrap_or(host)),
+            None => self.api_url.clone(),
+        }
+    }
+}
+
+// A named GitHub login such as "Personal" or "Work". Its token lives in the keyring under
+// github_credential_key(id).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitHubAccount {
+    pub id: String,
+    pub name: String,
+    pub username: Option<String>,
+    #[serde(default)]
+    pub endpoints: GitHubEndpoints,
+    // OAuth app the account signed in with; None for personal access tokens
+    #[serde(default)]
+    pub oauth_client_id: Option<String>,
+    // OAuth app tokens don't expire unless the app opted into expiring tokens, which can't be
+    // refreshed without the app's secret; the user signs in again instead
+    #[serde(default)]
+    pub token_expires_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GitHubConfig {
+    pub accounts: Vec<GitHubAccount>,
+}
+
+impl GitHubConfig {
+    pub fn account(&self, id: &str) -> Option<&GitHubAccount> {
+        self.accounts.iter().find(|a| a.id == id)
+    }
+}
+
+// What the user needs to sign in on GitHub: enter `user_code` at `verification_uri`, then
+// poll every `interval` seconds with `device_code` until GitHub hands out a token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitHubDeviceCode {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub expires_in: i64,
+    pub interval: i64,
+}
+
+// The account once sign-in completed, otherwise None and, if GitHub asked to poll less
+// often, the new interval in seconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitHubDeviceFlowPoll {
+    pub account: Option<GitHubAccount>,
+    pub interval: Option<i64>,
+}
+
+// A search capped at the configured number of items, with the number of matches on GitHub
+#[derive(Debug, Clone)]
+pub struct GitHubSearchResults<T> {
+    pub items: Vec<T>,
+    pub total_count: i64,
+}
+
+impl<T> Default for GitHubSearchResults<T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            total_count: 0,
+        }
+    }
+}
+
+// The user and their PRs and issues, however they were fetched
+#[derive(Debug, Clone)]
+pub struct GitHubOverview {
+    pub user: GitHubUser,
+    pub prs_to_review: GitHubSearchResults<GitHubPullRequest>,
+    pub my_open_prs: GitHubSearchResults<GitHubPullRequest>,
+    pub mentioned_issues: GitHubSearchResults<GitHubIssue>,
+}
+
+// Remaining budget for one GitHub rate-limit resource ("core", "search", "graphql")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitHubRateLimit {
+    pub account: String,
+    pub resource: String,
+    pub limit: i64,
+    pub remaining: i64,
+    pub used: i64,
+    pub reset: i64, // Unix time
+}
+
+// Matches reported by each search, which can exceed what was fetched ("20 of 57")
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GitHubTotals {
+    pub prs_to_review: i64,
+    pub my_open_prs: i64,
+    pub mentioned_issues: i64,
+}
+
+impl std::ops::AddAssign for GitHubTotals {
+    fn add_assign(&mut self, other: Self) {
+        self.prs_to_review += other.prs_to_review;
+        self.my_open_prs += other.my_open_prs;
+        self.mentioned_issues += other.mentioned_issues;
+    }
+}
+
+// Labels and per-account totals so the brief can be grouped or filtered by account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitHubAccountSummary {
+    pub id: String,
+    pub name: String,
+    pub username: Option<String>,
+    pub totals: GitHubTotals,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct GitHubBriefData {
     pub prs_to_review: Vec<GitHubPullRequest>,
     pub my_open_prs: Vec<GitHubPullRequest>,
     pub mentioned_issues: Vec<GitHubIssue>,
     pub notifications: Vec<GitHubNotification>,
+    pub totals: GitHubTotals, // Summed over accounts
+    #[serde(default)]
+    pub accounts: Vec<GitHubAccountSummary>,
     pub last_updated: Option<i64>,
+    // Set when this is cached data because GitHub's rate limit is used up
+    #[serde(default)]
+    pub rate_limited_until: Option<i64>,
 }
 
 // GitHub API response types
@@ -100,8 +270,12 @@ pub struct GitHubApiIssue {
     pub number: i32,
     pub title: String,
     pub state: String,
+    #[serde(default)]
+    pub draft: Option<bool>,
     pub user: GitHubApiUser,
     pub html_url: String,
+    pub created_at: String,
+    pub updated_at: String,
     pub body: Option<String>,
     pub labels: Vec<GitHubApiLabel>,
     pub pull_request: Option<serde_json::Value>,
@@ -109,10 +283,23 @@ pub struct GitHubApiIssue {
 
 #[derive(Debug, Deserialize)]
 pub struct GitHubApiSearchResult<T> {
-    pub total_count: i32,
+    pub total_count: i64,
     pub items: Vec<T>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct GitHubApiRateLimits {
+    pub resources: std::collections::HashMap<String, GitHubApiRateLimit>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitHubApiRateLimit {
+    pub limit: i64,
+    pub remaining: i64,
+    pub used: i64,
+    pub reset: i64,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct GitHubApiNotification {
     pub id: String,
@@ -135,3 +322,168 @@ pub struct GitHubApiSubject {
     pub subject_type: String,
     pub url: Option<String>,
 }
+
+// Answer of the OAuth token endpoint. GitHub reports failures with HTTP 200 and `error` set;
+// `expires_in` is only present for expiring tokens.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitHubOAuthToken {
+    pub access_token: Option<String>,
+    pub expires_in: Option<i64>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+    pub interval: Option<i64>,
+}
+
+// GitHub GraphQL response types
+#[derive(Debug, Deserialize)]
+pub struct GitHubGraphQlResponse<T> {
+    pub data: Option<T>,
+    #[serde(default)]
+    pub errors: Vec<GitHubGraphQlError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitHubGraphQlError {
+    pub message: String,
+    #[serde(rename = "type")]
+    pub error_type: Option<String>, // e.g. "RATE_LIMITED", "NOT_FOUND"
+}
+
+// Connections are read through `nodes`, which GraphQL allows to contain nulls
+#[derive(Debug, Deserialize)]
+pub struct GitHubGraphQlNodes<T> {
+    pub nodes: Vec<Option<T>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitHubGraphQlOverview {
+    pub viewer: GitHubGraphQlActor,
+    pub review_requested: GitHubGraphQlSearch<GitHubGraphQlPullRequestSummary>,
+    pub authored: GitHubGraphQlSearch<GitHubGraphQlPullRequestSummary>,
+    pub mentioned: GitHubGraphQlSearch<GitHubGraphQlIssue>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitHubGraphQlSearchPage<T> {
+    pub search: GitHubGraphQlSearch<T>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitHubGraphQlSearch<T> {
+    pub issue_count: i64,
+    pub page_info: GitHubGraphQlPageInfo,
+    pub nodes: Vec<Option<T>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitHubGraphQlPageInfo {
+    pub has_next_page: bool,
+    pub end_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitHubGraphQlActor {
+    pub login: String,
+    pub avatar_url: String,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitHubGraphQlLabel {
+    pub name: String,
+    pub color: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitHubGraphQlRepositoryName {
+    pub name_with_owner: String,
+}
+
+// fullDatabaseId is a BigInt, which GraphQL serializes as a string
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitHubGraphQlPullRequestSummary {
+    pub full_database_id: Option<String>,
+    pub number: i32,
+    pub title: String,
+    pub state: String,
+    pub url: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub author: Option<GitHubGraphQlActor>,
+    pub repository: GitHubGraphQlRepositoryName,
+    pub labels: GitHubGraphQlNodes<GitHubGraphQlLabel>,
+    #[serde(flatten)]
+    pub details: GitHubGraphQlPullRequest,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitHubGraphQlIssue {
+    pub full_database_id: Option<String>,
+    pub number: i32,
+    pub title: String,
+    pub state: String,
+    pub url: String,
+    pub body: Option<String>,
+    pub author: Option<GitHubGraphQlActor>,
+    pub repository: GitHubGraphQlRepositoryName,
+    pub labels: GitHubGraphQlNodes<GitHubGraphQlLabel>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitHubGraphQlRepository {
+    pub pull_request: Option<GitHubGraphQlPullRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitHubGraphQlPullRequest {
+    pub is_draft: bool,
+    pub review_decision: Option<String>,
+    pub mergeable: String,
+    pub latest_reviews: GitHubGraphQlNodes<GitHubGraphQlReview>,
+    pub review_requests: GitHubGraphQlNodes<GitHubGraphQlReviewRequest>,
+    pub commits: GitHubGraphQlNodes<GitHubGraphQlCommitNode>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitHubGraphQlReview {
+    pub state: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitHubGraphQlReviewRequest {
+    pub requested_reviewer: Option<GitHubGraphQlReviewer>,
+}
+
+// A user (login) or a team (slug)
+#[derive(Debug, Deserialize)]
+pub struct GitHubGraphQlReviewer {
+    pub login: Option<String>,
+    pub slug: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitHubGraphQlCommitNode {
+    pub commit: GitHubGraphQlCommit,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitHubGraphQlCommit {
+    pub status_check_rollup: Option<GitHubGraphQlStatusRollup>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitHubGraphQlStatusRollup {
+    pub state: String,
+}
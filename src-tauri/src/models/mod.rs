@@ -3,9 +3,13 @@ mod calendar;
 mod email;
 mod settings;
 mod brief;
+mod cache;
+mod schedule;
 
 pub use github::*;
 pub use calendar::*;
 pub use email::*;
 pub use settings::*;
 pub use brief::*;
+pub use cache::*;
+pub use schedule::*;
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use super::{CalendarEvent, CalendarTask};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeSlot {
+    pub start: i64,
+    pub end: i64,
+    pub duration_minutes: i64,
+}
+
+impl TimeSlot {
+    pub fn new(start: i64, end: i64) -> Self {
+        Self {
+            start,
+            end,
+            duration_minutes: (end - start) / 60,
+        }
+    }
+}
+
+// Meetings that follow each other with no real break in between
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackToBackChain {
+    pub start: i64,
+    pub end: i64,
+    pub event_ids: Vec<String>,
+}
+
+// Two events competing for the same time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarConflict {
+    pub kind: String, // "double_booking", "out_of_office"
+    pub start: i64,   // Start of the overlap
+    pub end: i64,
+    pub event_ids: Vec<String>,
+    pub summaries: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DaySchedule {
+    pub work_start: i64,
+    pub work_end: i64,
+    pub meeting_count: usize,
+    pub meeting_minutes: i64,
+    pub free_gaps: Vec<TimeSlot>,
+    pub back_to_back: Vec<BackToBackChain>,
+    pub longest_focus_block: Option<TimeSlot>,
+    // The first free gap that hasn't ended yet, clipped to start no earlier than now
+    pub next_free: Option<TimeSlot>,
+}
+
+// What to prepare for beyond today
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Lookahead {
+    pub next_day_start: i64, // The next working day; Monday when asked on a Friday
+    pub first_meeting: Option<CalendarEvent>,
+    pub meeting_count: usize,
+    // Open tasks due from tomorrow to the end of the week, or of the next working day if later
+    pub deadlines: Vec<CalendarTask>,
+}
@@ -1,12 +1,14 @@
 use serde::{Deserialize, Serialize};
-use super::{CalendarConfig, EmailConfig};
+use super::{CalendarConfig, EmailConfig, GitHubAccount};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AllSettings {
     pub github_configured: bool,
-    pub github_username: Option<String>,
+    pub github_accounts: Vec<GitHubAccount>,
+    pub github_max_items: usize,
     pub calendar_config: CalendarConfig,
     pub email_config: EmailConfig,
+    pub timezone: String,
     pub onboarding_complete: bool,
 }
 
@@ -14,9 +16,11 @@ impl Default for AllSettings {
     fn default() -> Self {
         Self {
             github_configured: false,
-            github_username: None,
+            github_accounts: Vec::new(),
+            github_max_items: super::DEFAULT_GITHUB_MAX_ITEMS,
             calendar_config: CalendarConfig::default(),
             email_config: EmailConfig::default(),
+            timezone: "UTC".to_string(),
             onboarding_complete: false,
         }
     }
@@ -0,0 +1,89 @@
+use std::collections::HashSet;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+use crate::error::Result;
+use crate::models::*;
+use crate::database;
+use crate::services;
+
+const CHECK_INTERVAL_SECONDS: u64 = 30;
+
+// After the machine wakes from sleep, reminders older than this are dropped instead of fired late
+const MAX_REMINDER_DELAY_SECONDS: i64 = 5 * 60;
+
+// VALARMs further ahead than this are not looked for
+const MAX_REMINDER_LEAD_SECONDS: i64 = 24 * 60 * 60;
+
+/// Starts the background task that turns cached events into desktop notifications and
+/// "meeting-reminder" events for the frontend.
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_check = chrono::Utc::now().timestamp();
+
+        loop {
+            tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL_SECONDS)).await;
+
+            let now = chrono::Utc::now().timestamp();
+            let from = last_check.max(now - MAX_REMINDER_DELAY_SECONDS);
+
+            match pending_reminders(&app, from, now) {
+                Ok(reminders) => {
+                    for reminder in reminders {
+                        notify(&app, &reminder);
+                    }
+                }
+                Err(e) => log::warn!("Failed to check meeting reminders: {}", e),
+            }
+
+            last_check = now;
+        }
+    });
+}
+
+fn pending_reminders(app: &AppHandle, from: i64, to: i64) -> Result<Vec<MeetingReminder>> {
+    let conn = database::open(app)?;
+    let config: CalendarConfig = database::get_setting(&conn, "calendar_config")?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
+    if !config.reminders_enabled {
+        return Ok(Vec::new());
+    }
+
+    // Alarms can fire before the start or, triggered after it, once the event has begun
+    let earliest_start = from - database::get_max_reminder_delay(&conn)?;
+
+    // Same event from several calendars should only ring once
+    let mut seen = HashSet::new();
+    let events: Vec<CalendarEvent> = database::get_calendar_events_starting_between(&conn, earliest_start, to + MAX_REMINDER_LEAD_SECONDS)?
+        .into_iter()
+        .filter(|event| config.source(&event.source).is_some_and(|s| s.enabled))
+        .filter(|event| seen.insert(event.id.clone()))
+        .map(|mut event| {
+            event.my_status = services::participation_status(&event, &config.my_emails);
+            event
+        })
+        .collect();
+
+    let default_lead = Some(config.default_reminder_minutes as i64 * 60).filter(|lead| *lead > 0);
+    Ok(services::due_reminders(&events, default_lead, from, to))
+}
+
+fn notify(app: &AppHandle, reminder: &MeetingReminder) {
+    let event = &reminder.event;
+    let minutes = (event.start_time - reminder.remind_at) / 60;
+    let body = match (minutes, &event.location) {
+        (m, Some(location)) if m > 0 => format!("Starts in {} min · {}", m, location),
+        (m, None) if m > 0 => format!("Starts in {} min", m),
+        (_, Some(location)) => format!("Starting now · {}", location),
+        (_, None) => "Starting now".to_string(),
+    };
+
+    if let Err(e) = app.notification().builder().title(&event.summary).body(body).show() {
+        log::warn!("Failed to show reminder for '{}': {}", event.summary, e);
+    }
+    if let Err(e) = app.emit("meeting-reminder", reminder) {
+        log::warn!("Failed to emit reminder for '{}': {}", event.summary, e);
+    }
+}
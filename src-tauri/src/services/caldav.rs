@@ -0,0 +1,685 @@
+use chrono::DateTime;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use reqwest::header::{HeaderValue, CONTENT_TYPE, LOCATION};
+use reqwest::redirect::Policy;
+use reqwest::{Method, Url};
+use crate::error::{AppError, Result};
+
+const PROPFIND_DISCOVERY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:resourcetype/>
+    <d:displayname/>
+    <d:current-user-principal/>
+    <c:calendar-home-set/>
+    <c:supported-calendar-component-set/>
+  </d:prop>
+</d:propfind>"#;
+
+// Redirects are followed by hand, since reqwest would replay PROPFIND and REPORT as GET
+const MAX_REDIRECTS: usize = 5;
+
+// One <response> element of a WebDAV multistatus body
+#[derive(Debug, Default)]
+struct DavResponse {
+    href: String,
+    resource_types: Vec<String>,
+    components: Vec<String>,
+    principal: Option<String>,
+    home_set: Option<String>,
+    calendar_data: Option<String>,
+}
+
+impl DavResponse {
+    fn is_calendar(&self) -> bool {
+        self.resource_types.iter().any(|t| t == "calendar") && (self.supports("VEVENT") || self.supports("VTODO"))
+    }
+
+    // Collections that don't list their components accept all of them
+    fn supports(&self, component: &str) -> bool {
+        self.components.is_empty() || self.components.iter().any(|c| c == component)
+    }
+
+    // Takes the properties a <propstat> reported as found
+    fn merge(&mut self, props: DavResponse) {
+        self.resource_types.extend(props.resource_types);
+        self.components.extend(props.components);
+        self.principal = self.principal.take().or(props.principal);
+        self.home_set = self.home_set.take().or(props.home_set);
+        self.calendar_data = self.calendar_data.take().or(props.calendar_data);
+    }
+
+    fn into_calendar(self, origin: &Url) -> Result<CalDavCalendar> {
+        Ok(CalDavCalendar {
+            url: resolve(origin, &self.href)?,
+            has_events: self.supports("VEVENT"),
+            has_tasks: self.supports("VTODO"),
+        })
+    }
+}
+
+/// A calendar collection and the components it holds; task lists are often separate
+/// collections without events.
+pub struct CalDavCalendar {
+    pub url: Url,
+    pub has_events: bool,
+    pub has_tasks: bool,
+}
+
+pub struct CalDavClient {
+    client: reqwest::Client,
+    base_url: Url,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl CalDavClient {
+    pub fn new(url: &str, username: Option<String>, password: Option<String>) -> Result<Self> {
+        let base_url = Url::parse(url)
+            .map_err(|e| AppError::Validation(format!("Invalid CalDAV URL '{}': {}", url, e)))?;
+
+        let client = reqwest::Client::builder()
+            .redirect(Policy::none())
+            .build()?;
+
+        Ok(Self {
+            client,
+            base_url,
+            username,
+            password,
+        })
+    }
+
+    /// Finds the event calendars and task lists reachable from the configured URL, which may
+    /// point at a calendar collection, a principal, a calendar home or just the server root.
+    pub async fn discover_calendars(&self) -> Result<Vec<CalDavCalendar>> {
+        // Hrefs are resolved against the URL that finally answered, not the one configured
+        let (mut origin, mut responses) = self.propfind(&self.base_url, 0, PROPFIND_DISCOVERY).await?;
+
+        if let Some(calendar) = responses.iter().find(|r| r.is_calendar()) {
+            return Ok(vec![CalDavCalendar {
+                url: origin,
+                has_events: calendar.supports("VEVENT"),
+                has_tasks: calendar.supports("VTODO"),
+            }]);
+        }
+
+        // Servers that only answer discovery on the well-known path (RFC 6764), which
+        // usually redirects to the principal or the DAV root
+        if !responses.iter().any(|r| r.principal.is_some() || r.home_set.is_some()) {
+            let well_known = resolve(&self.base_url, "/.well-known/caldav")?;
+            (origin, responses) = self
+                .propfind(&well_known, 0, PROPFIND_DISCOVERY)
+                .await
+                .unwrap_or((well_known, Vec::new()));
+        }
+
+        let mut home_set = responses.iter().find_map(|r| r.home_set.clone());
+        if home_set.is_none() {
+            let principal = responses
+                .iter()
+                .find_map(|r| r.principal.clone())
+                .ok_or_else(|| AppError::NotFound("CalDAV principal not found".to_string()))?;
+            let principal_url = resolve(&origin, &principal)?;
+            let (url, found) = self.propfind(&principal_url, 0, PROPFIND_DISCOVERY).await?;
+            origin = url;
+            home_set = found.into_iter().find_map(|r| r.home_set);
+        }
+
+        let home_set = home_set
+            .ok_or_else(|| AppError::NotFound("CalDAV calendar home not found".to_string()))?;
+        let home_url = resolve(&origin, &home_set)?;
+
+        let (origin, calendars) = self.propfind(&home_url, 1, PROPFIND_DISCOVERY).await?;
+        calendars
+            .into_iter()
+            .filter(|r| r.is_calendar())
+            .map(|r| r.into_calendar(&origin))
+            .collect()
+    }
+
+    /// Runs a calendar-query REPORT for events overlapping [start, end) and returns the
+    /// raw iCalendar objects.
+    pub async fn fetch_calendar_data(&self, collection: &Url, start: i64, end: i64) -> Result<Vec<String>> {
+        let filter = format!(
+            r#"<c:comp-filter name="VEVENT">
+        <c:time-range start="{}" end="{}"/>
+      </c:comp-filter>"#,
+            format_utc(start),
+            format_utc(end)
+        );
+        self.calendar_query(collection, &filter).await
+    }
+
+    /// Runs a calendar-query REPORT for tasks that aren't completed, whatever their due
+    /// date, since overdue and undated tasks belong in the brief too.
+    pub async fn fetch_task_data(&self, collection: &Url) -> Result<Vec<String>> {
+        let filter = r#"<c:comp-filter name="VTODO">
+        <c:prop-filter name="COMPLETED">
+          <c:is-not-defined/>
+        </c:prop-filter>
+      </c:comp-filter>"#;
+        self.calendar_query(collection, filter).await
+    }
+
+    async fn calendar_query(&self, collection: &Url, comp_filter: &str) -> Result<Vec<String>> {
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:getetag/>
+    <c:calendar-data/>
+  </d:prop>
+  <c:filter>
+    <c:comp-filter name="VCALENDAR">
+      {}
+    </c:comp-filter>
+  </c:filter>
+</c:calendar-query>"#,
+            comp_filter
+        );
+
+        let report = Method::from_bytes(b"REPORT").map_err(|e| AppError::Other(e.to_string()))?;
+        let (_, responses) = self.send(report, collection, 1, body).await?;
+
+        Ok(responses.into_iter().filter_map(|r| r.calendar_data).collect())
+    }
+
+    async fn propfind(&self, url: &Url, depth: u8, body: &str) -> Result<(Url, Vec<DavResponse>)> {
+        let propfind = Method::from_bytes(b"PROPFIND").map_err(|e| AppError::Other(e.to_string()))?;
+        self.send(propfind, url, depth, body.to_string()).await
+    }
+
+    // Sends a WebDAV request, re-issuing it with the same method and body on redirects, and
+    // returns the URL that answered along with the parsed multistatus
+    async fn send(&self, method: Method, url: &Url, depth: u8, body: String) -> Result<(Url, Vec<DavResponse>)> {
+        let mut url = url.clone();
+        let mut redirects = 0;
+        let mut with_credentials = true;
+
+        let response = loop {
+            let mut request = self
+                .client
+                .request(method.clone(), url.clone())
+                .header("Depth", depth.to_string())
+                .header(CONTENT_TYPE, HeaderValue::from_static("application/xml; charset=utf-8"))
+                .body(body.clone());
+
+            if let Some(username) = self.username.as_ref().filter(|_| with_credentials) {
+                request = request.basic_auth(username, self.password.as_deref());
+            }
+
+            let response = request.send().await?;
+            if !response.status().is_redirection() {
+                break response;
+            }
+
+            redirects += 1;
+            if redirects > MAX_REDIRECTS {
+                return Err(AppError::Other(format!("CalDAV request to {} redirected too many times", url)));
+            }
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|l| l.to_str().ok())
+                .ok_or_else(|| AppError::Other(format!("CalDAV redirect from {} has no location", url)))?;
+            let next = resolve(&url, location)?;
+            with_credentials &= keeps_credentials(&url, &next);
+            url = next;
+        };
+
+        let status = response.status();
+
+        if status.as_u16() == 401 {
+            return Err(AppError::Validation("CalDAV server rejected the credentials".to_string()));
+        }
+        if !status.is_success() {
+            return Err(AppError::Other(format!("CalDAV request to {} failed: {}", url, status)));
+        }
+
+        let text = response.text().await?;
+        Ok((url, parse_multistatus(&text)?))
+    }
+}
+
+// Credentials never go out in the clear after a redirect away from https
+fn keeps_credentials(from: &Url, to: &Url) -> bool {
+    from.scheme() != "https" || to.scheme() == "https"
+}
+
+fn resolve(base: &Url, href: &str) -> Result<Url> {
+    base.join(href)
+        .map_err(|e| AppError::Parse(format!("Invalid CalDAV href '{}': {}", href, e)))
+}
+
+fn format_utc(ts: i64) -> String {
+    DateTime::from_timestamp(ts, 0)
+        .unwrap_or_default()
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+// Parses a multistatus body by local element names, since servers pick their own prefixes.
+// Properties in a <propstat> with a non-2xx status were not found and are skipped.
+fn parse_multistatus(xml: &str) -> Result<Vec<DavResponse>> {
+    let mut reader = Reader::from_str(xml);
+    let mut stack: Vec<String> = Vec::new();
+    let mut responses = Vec::new();
+    let mut current: Option<DavResponse> = None;
+    let mut props: Option<DavResponse> = None;
+    let mut props_found = false;
+    let mut text = String::new();
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| AppError::Parse(format!("CalDAV XML error: {}", e)))?;
+
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                let parent = stack.last().map(String::as_str);
+
+                if name == "response" {
+                    current = Some(DavResponse::default());
+                }
+                // A propstat without a status is taken as found
+                if name == "propstat" {
+                    props = Some(DavResponse::default());
+                    props_found = true;
+                }
+                if let Some(response) = props.as_mut().or(current.as_mut()) {
+                    if parent == Some("resourcetype") {
+                        response.resource_types.push(name.clone());
+                    }
+                    if name == "comp" && parent == Some("supported-calendar-component-set") {
+                        let component = e
+                            .try_get_attribute("name")
+                            .ok()
+                            .flatten()
+                            .and_then(|a| a.unescape_value().ok().map(|v| v.to_string()));
+                        response.components.extend(component);
+                    }
+                }
+
+                text.clear();
+                if matches!(event, Event::Start(_)) {
+                    stack.push(name);
+                }
+            }
+            Event::Text(ref e) => {
+                text.push_str(&e.decode().map_err(|e| AppError::Parse(e.to_string()))?);
+            }
+            Event::CData(ref e) => {
+                text.push_str(&e.decode().map_err(|e| AppError::Parse(e.to_string()))?);
+            }
+            Event::GeneralRef(ref e) => {
+                if let Ok(Some(ch)) = e.resolve_char_ref() {
+                    text.push(ch);
+                } else if let Ok(name) = e.decode() {
+                    text.push_str(resolve_predefined_entity(&name).unwrap_or_default());
+                }
+            }
+            Event::End(_) => {
+                let name = stack.pop().unwrap_or_default();
+                let parent = stack.last().map(String::as_str);
+
+                match (name.as_str(), parent) {
+                    ("href", Some("response")) => {
+                        if let Some(response) = current.as_mut() {
+                            response.href = text.trim().to_string();
+                        }
+                    }
+                    ("status", Some("propstat")) => {
+                        props_found = text.split_whitespace().nth(1).is_some_and(|code| code.starts_with('2'));
+                    }
+                    ("propstat", _) => {
+                        if let (Some(response), Some(found)) = (current.as_mut(), props.take()) {
+                            if props_found {
+                                response.merge(found);
+                            }
+                        }
+                    }
+                    (name, parent) => {
+                        if let Some(response) = props.as_mut().or(current.as_mut()) {
+                            match (name, parent) {
+                                ("href", Some("current-user-principal")) => {
+                                    response.principal = Some(text.trim().to_string())
+                                }
+                                ("href", Some("calendar-home-set")) => response.home_set = Some(text.trim().to_string()),
+                                ("calendar-data", _) => response.calendar_data = Some(text.clone()),
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+                if name == "response" {
+                    responses.extend(current.take());
+                }
+                text.clear();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(responses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    // A request as the stub server received it
+    #[derive(Debug, Clone)]
+    struct Received {
+        method: String,
+        path: String,
+        depth: Option<String>,
+        authorization: Option<String>,
+        body: String,
+    }
+
+    enum Reply {
+        Multistatus(String),
+        Redirect(&'static str),
+        NotFound,
+    }
+
+    // Answers every request on a local port with `route`, recording what it was sent
+    async fn stub_server(route: fn(&Received) -> Reply) -> (Url, Arc<Mutex<Vec<Received>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let log = log.clone();
+                tokio::spawn(async move {
+                    let mut stream = BufReader::new(stream);
+                    let mut line = String::new();
+                    stream.read_line(&mut line).await.unwrap();
+                    let mut parts = line.split_whitespace();
+                    let method = parts.next().unwrap_or_default().to_string();
+                    let path = parts.next().unwrap_or_default().to_string();
+
+                    let (mut length, mut depth, mut authorization) = (0, None, None);
+                    loop {
+                        line.clear();
+                        stream.read_line(&mut line).await.unwrap();
+                        let Some((name, value)) = line.trim_end().split_once(':') else { break };
+                        match name.to_ascii_lowercase().as_str() {
+                            "content-length" => length = value.trim().parse().unwrap(),
+                            "depth" => depth = Some(value.trim().to_string()),
+                            "authorization" => authorization = Some(value.trim().to_string()),
+                            _ => {}
+                        }
+                    }
+                    let mut body = vec![0; length];
+                    stream.read_exact(&mut body).await.unwrap();
+
+                    let request = Received {
+                        method,
+                        path,
+                        depth,
+                        authorization,
+                        body: String::from_utf8(body).unwrap(),
+                    };
+                    let response = match route(&request) {
+                        Reply::Multistatus(xml) => format!(
+                            "HTTP/1.1 207 Multi-Status\r\nContent-Type: application/xml\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            xml.len(),
+                            xml
+                        ),
+                        Reply::Redirect(location) => format!(
+                            "HTTP/1.1 301 Moved Permanently\r\nLocation: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                            location
+                        ),
+                        Reply::NotFound => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+                    };
+                    log.lock().unwrap().push(request);
+                    stream.get_mut().write_all(response.as_bytes()).await.unwrap();
+                });
+            }
+        });
+
+        (url, received)
+    }
+
+    fn multistatus(responses: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<d:multistatus xmlns:d="DAV:" xmlns:cal="urn:ietf:params:xml:ns:caldav">{}</d:multistatus>"#,
+            responses
+        )
+    }
+
+    fn found(href: &str, props: &str) -> String {
+        format!(
+            "<d:response><d:href>{}</d:href><d:propstat><d:prop>{}</d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>",
+            href, props
+        )
+    }
+
+    fn calendar(href: &str, component: &str) -> String {
+        found(
+            href,
+            &format!(
+                r#"<d:resourcetype><d:collection/><cal:calendar/></d:resourcetype>
+<cal:supported-calendar-component-set><cal:comp name="{}"/></cal:supported-calendar-component-set>"#,
+                component
+            ),
+        )
+    }
+
+    // Root without discovery properties, a well-known redirect, then principal and home set
+    fn discovery_route(request: &Received) -> Reply {
+        match (request.method.as_str(), request.path.as_str()) {
+            ("PROPFIND", "/") => Reply::Multistatus(multistatus(&found(
+                "/",
+                "<d:resourcetype><d:collection/></d:resourcetype>",
+            ))),
+            (_, "/.well-known/caldav") => Reply::Redirect("/dav/"),
+            ("PROPFIND", "/dav/") => Reply::Multistatus(multistatus(&found(
+                "/dav/",
+                "<d:current-user-principal><d:href>/dav/principals/alice/</d:href></d:current-user-principal>",
+            ))),
+            ("PROPFIND", "/dav/principals/alice/") => Reply::Multistatus(multistatus(&found(
+                "/dav/principals/alice/",
+                "<cal:calendar-home-set><d:href>/dav/calendars/alice/</d:href></cal:calendar-home-set>",
+            ))),
+            ("PROPFIND", "/dav/calendars/alice/") => Reply::Multistatus(multistatus(&[
+                found("/dav/calendars/alice/", "<d:resourcetype><d:collection/></d:resourcetype>"),
+                calendar("/dav/calendars/alice/work/", "VEVENT"),
+                calendar("/dav/calendars/alice/todo/", "VTODO"),
+                calendar("/dav/calendars/alice/journal/", "VJOURNAL"),
+            ]
+            .concat())),
+            ("REPORT", "/dav/calendars/alice/work/") => Reply::Multistatus(multistatus(&[
+                found(
+                    "/dav/calendars/alice/work/standup.ics",
+                    "<d:getetag>\"1\"</d:getetag><cal:calendar-data>BEGIN:VCALENDAR\r\nSUMMARY:R&amp;D sync\r\nEND:VCALENDAR\r\n</cal:calendar-data>",
+                ),
+                found("/dav/calendars/alice/work/empty.ics", "<d:getetag>\"2\"</d:getetag>"),
+            ]
+            .concat())),
+            _ => Reply::NotFound,
+        }
+    }
+
+    fn client(url: &Url) -> CalDavClient {
+        CalDavClient::new(url.as_str(), Some("alice".to_string()), Some("secret".to_string())).unwrap()
+    }
+
+    #[test]
+    fn parses_any_namespace_prefix() {
+        let xml = r#"<?xml version="1.0"?>
+<multistatus xmlns="DAV:">
+  <response>
+    <href>/calendars/alice/</href>
+    <propstat>
+      <prop>
+        <current-user-principal><href>/principals/alice/</href></current-user-principal>
+        <C:calendar-home-set xmlns:C="urn:ietf:params:xml:ns:caldav"><href>/calendars/alice/</href></C:calendar-home-set>
+        <resourcetype><collection/><C:calendar xmlns:C="urn:ietf:params:xml:ns:caldav"/></resourcetype>
+        <X:supported-calendar-component-set xmlns:X="urn:ietf:params:xml:ns:caldav">
+          <X:comp name="VEVENT"/><X:comp name="VTODO"/>
+        </X:supported-calendar-component-set>
+      </prop>
+      <status>HTTP/1.1 200 OK</status>
+    </propstat>
+  </response>
+</multistatus>"#;
+
+        let responses = parse_multistatus(xml).unwrap();
+        assert_eq!(responses.len(), 1);
+        let response = &responses[0];
+        assert_eq!(response.href, "/calendars/alice/");
+        assert_eq!(response.principal.as_deref(), Some("/principals/alice/"));
+        assert_eq!(response.home_set.as_deref(), Some("/calendars/alice/"));
+        assert_eq!(response.resource_types, vec!["collection", "calendar"]);
+        assert_eq!(response.components, vec!["VEVENT", "VTODO"]);
+    }
+
+    #[test]
+    fn ignores_properties_from_a_404_propstat() {
+        let xml = multistatus(
+            r#"<d:response>
+  <d:href>/dav/</d:href>
+  <d:propstat>
+    <d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop>
+    <d:status>HTTP/1.1 200 OK</d:status>
+  </d:propstat>
+  <d:propstat>
+    <d:prop>
+      <cal:calendar-home-set><d:href>/not/really/</d:href></cal:calendar-home-set>
+      <cal:calendar-data></cal:calendar-data>
+      <d:resourcetype><cal:calendar/></d:resourcetype>
+    </d:prop>
+    <d:status>HTTP/1.1 404 Not Found</d:status>
+  </d:propstat>
+</d:response>"#,
+        );
+
+        let responses = parse_multistatus(&xml).unwrap();
+        assert_eq!(responses[0].resource_types, vec!["collection"]);
+        assert!(responses[0].home_set.is_none());
+        assert!(responses[0].calendar_data.is_none());
+        assert!(!responses[0].is_calendar());
+    }
+
+    #[test]
+    fn leaves_missing_calendar_data_empty() {
+        let xml = multistatus(&[
+            found("/work/a.ics", "<d:getetag>\"a\"</d:getetag><cal:calendar-data><![CDATA[BEGIN:VCALENDAR]]></cal:calendar-data>"),
+            found("/work/b.ics", "<d:getetag>\"b\"</d:getetag>"),
+        ]
+        .concat());
+
+        let responses = parse_multistatus(&xml).unwrap();
+        assert_eq!(responses[0].calendar_data.as_deref(), Some("BEGIN:VCALENDAR"));
+        assert!(responses[1].calendar_data.is_none());
+    }
+
+    #[test]
+    fn drops_credentials_after_a_redirect_to_http() {
+        let url = |s: &str| Url::parse(s).unwrap();
+        assert!(!keeps_credentials(&url("https://dav.example.com/"), &url("http://dav.example.com/dav/")));
+        assert!(keeps_credentials(&url("https://example.com/"), &url("https://dav.example.com/dav/")));
+        assert!(keeps_credentials(&url("http://localhost:5232/"), &url("http://localhost:5232/dav/")));
+    }
+
+    #[tokio::test]
+    async fn discovers_calendars_through_the_well_known_redirect() {
+        let (url, received) = stub_server(discovery_route).await;
+
+        let calendars = client(&url).discover_calendars().await.unwrap();
+        let found: Vec<(String, bool, bool)> = calendars
+            .iter()
+            .map(|c| (c.url.path().to_string(), c.has_events, c.has_tasks))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("/dav/calendars/alice/work/".to_string(), true, false),
+                ("/dav/calendars/alice/todo/".to_string(), false, true),
+            ]
+        );
+
+        let received = received.lock().unwrap();
+        let requests: Vec<(&str, &str)> = received.iter().map(|r| (r.method.as_str(), r.path.as_str())).collect();
+        assert_eq!(
+            requests,
+            vec![
+                ("PROPFIND", "/"),
+                ("PROPFIND", "/.well-known/caldav"),
+                // Re-issued as a PROPFIND, not replayed as a GET
+                ("PROPFIND", "/dav/"),
+                ("PROPFIND", "/dav/principals/alice/"),
+                ("PROPFIND", "/dav/calendars/alice/"),
+            ]
+        );
+        assert_eq!(received[2].body, PROPFIND_DISCOVERY);
+        assert_eq!(received[4].depth.as_deref(), Some("1"));
+        // alice:secret, still sent after the same-scheme redirect
+        assert!(received.iter().all(|r| r.authorization.as_deref() == Some("Basic YWxpY2U6c2VjcmV0")));
+    }
+
+    #[tokio::test]
+    async fn uses_a_configured_calendar_directly() {
+        fn route(request: &Received) -> Reply {
+            match request.path.as_str() {
+                "/dav/calendars/alice/work/" => Reply::Multistatus(multistatus(&calendar(&request.path, "VEVENT"))),
+                _ => Reply::NotFound,
+            }
+        }
+        let (url, received) = stub_server(route).await;
+
+        let collection = url.join("/dav/calendars/alice/work/").unwrap();
+        let calendars = client(&collection).discover_calendars().await.unwrap();
+        assert_eq!(calendars.len(), 1);
+        assert_eq!(calendars[0].url, collection);
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn queries_events_in_the_time_range() {
+        let (url, received) = stub_server(discovery_route).await;
+        let collection = url.join("/dav/calendars/alice/work/").unwrap();
+
+        // 2026-01-01T00:00:00Z to 2026-01-09T00:00:00Z
+        let data = client(&url)
+            .fetch_calendar_data(&collection, 1_767_225_600, 1_767_916_800)
+            .await
+            .unwrap();
+        assert_eq!(data, vec!["BEGIN:VCALENDAR\r\nSUMMARY:R&D sync\r\nEND:VCALENDAR\r\n"]);
+
+        let received = received.lock().unwrap();
+        assert_eq!(received[0].method, "REPORT");
+        assert_eq!(received[0].depth.as_deref(), Some("1"));
+        assert!(received[0].body.contains(r#"<c:comp-filter name="VEVENT">"#));
+        assert!(received[0]
+            .body
+            .contains(r#"<c:time-range start="20260101T000000Z" end="20260109T000000Z"/>"#));
+    }
+
+    #[tokio::test]
+    async fn queries_open_tasks_without_a_time_range() {
+        let (url, received) = stub_server(discovery_route).await;
+        let collection = url.join("/dav/calendars/alice/todo/").unwrap();
+
+        client(&url).fetch_task_data(&collection).await.unwrap_err();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received[0].method, "REPORT");
+        assert!(received[0].body.contains(r#"<c:comp-filter name="VTODO">"#));
+        assert!(received[0].body.contains(r#"<c:prop-filter name="COMPLETED">"#));
+        assert!(!received[0].body.contains("time-range"));
+    }
+}
@@ -1,21 +1,241 @@
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::BufReader;
-use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc, Local};
-use ical::parser::ical::component::IcalEvent;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::SystemTime;
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use ical::parser::ical::component::{IcalAlarm, IcalEvent, IcalTodo};
+use ical::property::Property;
 use ical::IcalParser;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
 use crate::error::{AppError, Result};
-use crate::models::CalendarEvent;
+use crate::models::{CacheValidators, CalendarEvent, CalendarSource, CalendarTask, EventAttendee};
+use super::caldav::CalDavClient;
+use super::conference::{detect_conference, Conference};
+use super::credentials::{calendar_credential_key, get_credential};
+use super::recurrence::RecurrenceRule;
+use super::timezone::{start_of_day, ResolvedZone, TimeZoneResolver};
 
-pub struct CalendarService;
+// Days of events expanded per fetch, starting today
+const BRIEF_WINDOW_DAYS: i64 = 8;
+
+pub struct CalendarService {
+    // The user's zone: defines "today" and anchors all-day and floating times
+    timezone: Tz,
+    // Events are expanded into [start, end): the brief window unless asked for another
+    window: (i64, i64),
+}
+
+// How a DTSTART/DTEND-style value should be anchored to an instant
+#[derive(Debug, Clone)]
+enum IcsZone {
+    Utc,
+    Zone(ResolvedZone),
+}
+
+impl IcsZone {
+    fn to_timestamp(&self, value: NaiveDateTime) -> i64 {
+        match self {
+            IcsZone::Utc => value.and_utc().timestamp(),
+            IcsZone::Zone(zone) => zone.to_timestamp(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct IcsDateTime {
+    value: NaiveDateTime,
+    zone: IcsZone,
+}
+
+impl IcsDateTime {
+    fn to_timestamp(&self) -> i64 {
+        self.zone.to_timestamp(self.value)
+    }
+}
+
+// When a VALARM fires, as written in the feed
+#[derive(Debug, Clone)]
+enum AlarmTrigger {
+    Start(i64), // Seconds relative to the start; negative is before
+    End(i64),   // Seconds relative to the end (RELATED=END)
+    Absolute(i64),
+}
+
+// A VEVENT as written in the feed, before recurrence expansion
+struct RawEvent {
+    uid: String,
+    summary: Option<String>,
+    description: Option<String>,
+    location: Option<String>,
+    url: Option<String>,
+    conference: Option<Conference>,
+    organizer: Option<EventAttendee>,
+    attendees: Vec<EventAttendee>,
+    out_of_office: bool,
+    alarms: Vec<AlarmTrigger>,
+    start: IcsDateTime,
+    end: Option<IcsDateTime>,
+    duration: Option<i64>,
+    all_day: bool,
+    rrule: Option<RecurrenceRule>,
+    rdates: Vec<IcsDateTime>,
+    exdates: Vec<IcsDateTime>,
+    recurrence_id: Option<IcsDateTime>,
+    cancelled: bool,
+}
+
+// Brief window: today and the coming week in the user's timezone, so the lookahead can
+// reach the next working day and the week's deadlines
+fn brief_window(timezone: Tz) -> (i64, i64) {
+    let today = Utc::now().with_timezone(&timezone).date_naive();
+    (
+        start_of_day(timezone, today),
+        start_of_day(timezone, today + Duration::days(BRIEF_WINDOW_DAYS)),
+    )
+}
+
+// Everything taken from one feed: event instances in the service's window and open tasks
+#[derive(Debug, Clone, Default)]
+pub struct CalendarData {
+    pub events: Vec<CalendarEvent>,
+    pub tasks: Vec<CalendarTask>,
+}
+
+// A feed as parsed, before recurrence expansion, so it can be expanded into any window
+#[derive(Default)]
+struct ParsedIcs {
+    // Each master event with the RECURRENCE-ID overrides of its instances
+    series: Vec<(RawEvent, Vec<RawEvent>)>,
+    // Overrides whose master is missing from the feed; they still describe a concrete instance
+    orphans: Vec<RawEvent>,
+    tasks: Vec<CalendarTask>,
+}
+
+// One parsed file of a vdir source, reused until the file changes
+struct ParsedFile {
+    modified: SystemTime,
+    len: u64,
+    timezone: Tz,
+    parsed: ParsedIcs,
+}
+
+// Parsed files keyed by vdir directory, then by file path
+static VDIR_CACHE: LazyLock<Mutex<HashMap<PathBuf, HashMap<PathBuf, ParsedFile>>>> =
+    LazyLock::new(Default::default);
+
+// Result of fetching a source that may answer a conditional request with 304 Not Modified
+pub enum SourceFetch {
+    Updated(CalendarData, CacheValidators),
+    NotModified,
+}
 
 impl CalendarService {
-    pub fn new() -> Self {
-        Self
+    pub fn new(timezone: Tz) -> Self {
+        Self { timezone, window: brief_window(timezone) }
+    }
+
+    /// A service that expands events into [start, end) instead of the brief window, for
+    /// looking at dates the cache doesn't cover.
+    pub fn with_window(timezone: Tz, start: i64, end: i64) -> Self {
+        Self { timezone, window: (start, end) }
+    }
+
+    pub fn window(&self) -> (i64, i64) {
+        self.window
     }
 
-    pub async fn fetch_from_url(&self, url: &str) -> Result<Vec<CalendarEvent>> {
+    // Fetches one configured source and tags its events with the source id and colour.
+    // `validators` from the previous fetch make URL sources send a conditional request.
+    pub async fn fetch_source(&self, source: &CalendarSource, validators: &CacheValidators) -> Result<SourceFetch> {
+        let (mut data, validators) = match source.source_type.as_str() {
+            "ics_url" => {
+                let url = source.ics_url.as_deref().ok_or_else(|| {
+                    AppError::Validation(format!("Calendar URL not configured for '{}'", source.name))
+                })?;
+                let secret = get_credential(&calendar_credential_key(&source.id))?;
+                match self.fetch_from_url(url, source.username.clone(), secret, validators).await? {
+                    SourceFetch::Updated(data, validators) => (data, validators),
+                    SourceFetch::NotModified => return Ok(SourceFetch::NotModified),
+                }
+            }
+            "ics_file" => {
+                let path = source.ics_path.as_deref().ok_or_else(|| {
+                    AppError::Validation(format!("Calendar file path not configured for '{}'", source.name))
+                })?;
+                (self.parse_from_file(path)?, CacheValidators::default())
+            }
+            "vdir" => {
+                let path = source.ics_path.as_deref().ok_or_else(|| {
+                    AppError::Validation(format!("Calendar directory not configured for '{}'", source.name))
+                })?;
+                (self.parse_from_directory(path)?, CacheValidators::default())
+            }
+            "caldav" => {
+                let url = source.caldav_url.as_deref().ok_or_else(|| {
+                    AppError::Validation(format!("CalDAV URL not configured for '{}'", source.name))
+                })?;
+                let password = get_credential(&calendar_credential_key(&source.id))?;
+                let data = self.fetch_from_caldav(url, source.username.clone(), password).await?;
+                (data, CacheValidators::default())
+            }
+            other => {
+                return Err(AppError::Validation(format!("Unknown calendar source type: {}", other)));
+            }
+        };
+
+        for event in &mut data.events {
+            event.source = source.id.clone();
+            event.color = source.color.clone();
+        }
+        for task in &mut data.tasks {
+            task.source = source.id.clone();
+            task.color = source.color.clone();
+        }
+
+        Ok(SourceFetch::Updated(data, validators))
+    }
+
+    /// Fetches an ICS feed. With a username the secret is sent as a basic-auth password,
+    /// without one as a bearer token.
+    pub async fn fetch_from_url(
+        &self,
+        url: &str,
+        username: Option<String>,
+        secret: Option<String>,
+        validators: &CacheValidators,
+    ) -> Result<SourceFetch> {
         let client = reqwest::Client::new();
-        let response = client.get(url).send().await?;
+        let mut request = client.get(normalize_feed_url(url));
+
+        match (username, secret) {
+            (Some(username), secret) => request = request.basic_auth(username, secret),
+            (None, Some(token)) => request = request.bearer_auth(token),
+            (None, None) => {}
+        }
+
+        if let Some(ref etag) = validators.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(ref last_modified) = validators.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+
+        let response = request.send().await?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(SourceFetch::NotModified);
+        }
+
+        if matches!(response.status(), StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+            return Err(AppError::Validation(format!(
+                "Calendar feed rejected the credentials: {}",
+                response.status()
+            )));
+        }
 
         if !response.status().is_success() {
             return Err(AppError::Other(format!(
@@ -24,53 +244,249 @@ impl CalendarService {
             )));
         }
 
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from)
+        };
+        let validators = CacheValidators {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            poll_interval: None,
+        };
+
         let content = response.text().await?;
-        self.parse_ics_content(&content)
+        Ok(SourceFetch::Updated(self.parse_ics_content(&content)?, validators))
+    }
+
+    pub async fn fetch_from_caldav(
+        &self,
+        url: &str,
+        username: Option<String>,
+        password: Option<String>,
+    ) -> Result<CalendarData> {
+        let client = CalDavClient::new(url, username, password)?;
+        let (window_start, window_end) = self.window;
+
+        let mut result = CalendarData::default();
+        for calendar in client.discover_calendars().await? {
+            if calendar.has_events {
+                for data in client.fetch_calendar_data(&calendar.url, window_start, window_end).await? {
+                    result.events.extend(self.parse_ics_content(&data)?.events);
+                }
+            }
+            if calendar.has_tasks {
+                for data in client.fetch_task_data(&calendar.url).await? {
+                    result.tasks.extend(self.parse_ics_content(&data)?.tasks);
+                }
+            }
+        }
+
+        sort_events(&mut result.events);
+        Ok(result)
     }
 
-    pub fn parse_from_file(&self, path: &str) -> Result<Vec<CalendarEvent>> {
+    pub fn parse_from_file(&self, path: &str) -> Result<CalendarData> {
         let content = fs::read_to_string(path)?;
         self.parse_ics_content(&content)
     }
 
-    fn parse_ics_content(&self, content: &str) -> Result<Vec<CalendarEvent>> {
+    /// Parses every `.ics` file under a vdir directory (the vdirsyncer/khal layout, one item
+    /// per file). Files whose mtime and size are unchanged since the last call are not re-read.
+    pub fn parse_from_directory(&self, path: &str) -> Result<CalendarData> {
+        let directory = Path::new(path);
+        if !directory.is_dir() {
+            return Err(AppError::Validation(format!("Not a directory: {}", path)));
+        }
+
+        let mut files = Vec::new();
+        collect_ics_files(directory, &mut HashSet::new(), &mut files)?;
+
+        let mut cache = VDIR_CACHE.lock().map_err(|e| AppError::Other(e.to_string()))?;
+        let mut previous = cache.remove(directory).unwrap_or_default();
+        let mut current = HashMap::new();
+        let mut result = CalendarData::default();
+
+        for (file, metadata) in files {
+            let modified = metadata.modified()?;
+            let len = metadata.len();
+
+            let parsed = match previous.remove(&file) {
+                Some(parsed)
+                    if parsed.modified == modified && parsed.len == len && parsed.timezone == self.timezone =>
+                {
+                    parsed
+                }
+                _ => {
+                    let parsed = fs::read_to_string(&file)
+                        .map_err(AppError::from)
+                        .and_then(|content| self.parse_ics(&content));
+                    match parsed {
+                        Ok(parsed) => ParsedFile { modified, len, timezone: self.timezone, parsed },
+                        // One broken item shouldn't hide the rest of the calendar
+                        Err(e) => {
+                            log::warn!("Skipping calendar file {}: {}", file.display(), e);
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            let data = self.expand_parsed(&parsed.parsed);
+            result.events.extend(data.events);
+            result.tasks.extend(data.tasks);
+            current.insert(file, parsed);
+        }
+
+        cache.insert(directory.to_path_buf(), current);
+
+        sort_events(&mut result.events);
+        Ok(result)
+    }
+
+    fn parse_ics_content(&self, content: &str) -> Result<CalendarData> {
+        Ok(self.expand_parsed(&self.parse_ics(content)?))
+    }
+
+    fn parse_ics(&self, content: &str) -> Result<ParsedIcs> {
         let buf = BufReader::new(content.as_bytes());
         let parser = IcalParser::new(buf);
-
-        let mut events = Vec::new();
-        let today = Utc::now().date_naive();
-        let tomorrow = today.succ_opt().unwrap_or(today);
+        let mut parsed = ParsedIcs::default();
 
         for calendar in parser {
             let calendar = calendar.map_err(|e| AppError::Parse(format!("iCal parse error: {}", e)))?;
+            let timezones = TimeZoneResolver::from_calendar(&calendar.timezones);
 
-            for event in calendar.events {
-                if let Some(parsed_event) = self.parse_event(&event, today, tomorrow) {
-                    events.push(parsed_event);
+            // Group by UID so RECURRENCE-ID overrides can replace instances of their master
+            let mut masters: Vec<RawEvent> = Vec::new();
+            let mut overrides: HashMap<String, Vec<RawEvent>> = HashMap::new();
+
+            for event in &calendar.events {
+                if let Some(raw) = self.parse_event(event, &timezones) {
+                    if raw.recurrence_id.is_some() {
+                        overrides.entry(raw.uid.clone()).or_default().push(raw);
+                    } else {
+                        masters.push(raw);
+                    }
                 }
             }
+
+            for master in masters {
+                let instance_overrides = overrides.remove(&master.uid).unwrap_or_default();
+                parsed.series.push((master, instance_overrides));
+            }
+            parsed.orphans.extend(overrides.into_values().flatten());
+
+            parsed.tasks.extend(calendar.todos.iter().filter_map(|todo| self.parse_todo(todo, &timezones)));
+        }
+
+        Ok(parsed)
+    }
+
+    // Expands parsed events into the service's window. Whether a task is overdue is worked
+    // out here too, since a parsed file can outlive the day it was read on.
+    fn expand_parsed(&self, parsed: &ParsedIcs) -> CalendarData {
+        let (window_start, window_end) = self.window;
+
+        let mut events = Vec::new();
+        for (master, overrides) in &parsed.series {
+            events.extend(self.expand_event(master, overrides, window_start, window_end));
+        }
+        for orphan in &parsed.orphans {
+            events.extend(self.expand_event(orphan, &[], window_start, window_end));
         }
+        sort_events(&mut events);
+
+        let now = Utc::now().timestamp();
+        let today = Utc::now().with_timezone(&self.timezone).date_naive();
+        let today_start = start_of_day(self.timezone, today);
+        let tasks = parsed
+            .tasks
+            .iter()
+            .map(|task| CalendarTask {
+                is_overdue: is_task_overdue(task.due_time, task.due_all_day, now, today_start),
+                ..task.clone()
+            })
+            .collect();
+
+        CalendarData { events, tasks }
+    }
+
+    // Open VTODOs only; completed and cancelled tasks never reach the brief
+    fn parse_todo(&self, todo: &IcalTodo, timezones: &TimeZoneResolver) -> Option<CalendarTask> {
+        let mut uid = None;
+        let mut summary = None;
+        let mut description = None;
+        let mut due = None;
+        let mut due_all_day = false;
+        let mut priority = None;
+        let mut status = "needs_action".to_string();
+        let mut percent_complete = None;
+        let mut completed = false;
 
-        // Sort: all-day events first, then by start time
-        events.sort_by(|a, b| {
-            match (a.all_day, b.all_day) {
-                (true, false) => std::cmp::Ordering::Less,
-                (false, true) => std::cmp::Ordering::Greater,
-                _ => a.start_time.cmp(&b.start_time),
+        for prop in &todo.properties {
+            match prop.name.as_str() {
+                "UID" => uid = prop.value.clone(),
+                "SUMMARY" => summary = prop.value.clone(),
+                "DESCRIPTION" => description = prop.value.clone(),
+                "DUE" => {
+                    if let Some(ref value) = prop.value {
+                        due_all_day = self.is_date_only(prop, value);
+                        due = self.parse_datetime(value, due_all_day, self.zone_for(prop, timezones));
+                    }
+                }
+                // 0 means undefined
+                "PRIORITY" => priority = prop.value.as_deref().and_then(|p| p.trim().parse().ok()).filter(|p| *p > 0),
+                "STATUS" => status = prop.value.as_deref().map(normalize_enum).unwrap_or(status),
+                "PERCENT-COMPLETE" => percent_complete = prop.value.as_deref().and_then(|p| p.trim().parse().ok()),
+                "COMPLETED" => completed = true,
+                _ => {}
             }
-        });
+        }
+
+        if completed || status == "completed" || status == "cancelled" || percent_complete == Some(100) {
+            return None;
+        }
 
-        Ok(events)
+        let due_time = due.map(|d| d.to_timestamp());
+
+        Some(CalendarTask {
+            id: uid?,
+            source: String::new(),
+            summary: summary.unwrap_or_else(|| "(No title)".to_string()),
+            description,
+            due_time,
+            due_all_day,
+            priority,
+            status,
+            percent_complete,
+            color: None,
+            is_overdue: false, // Worked out when expanded
+        })
     }
 
-    fn parse_event(&self, event: &IcalEvent, today: NaiveDate, tomorrow: NaiveDate) -> Option<CalendarEvent> {
+    fn parse_event(&self, event: &IcalEvent, timezones: &TimeZoneResolver) -> Option<RawEvent> {
         let mut uid = None;
         let mut summary = None;
         let mut description = None;
         let mut location = None;
+        let mut url = None;
+        let mut google_conference = None;
+        let mut microsoft_meeting = None;
+        let mut organizer = None;
+        let mut attendees = Vec::new();
+        let mut busy_status_oof = false;
         let mut dtstart = None;
         let mut dtend = None;
+        let mut duration = None;
         let mut all_day = false;
+        let mut rrule = None;
+        let mut rdates = Vec::new();
+        let mut exdates = Vec::new();
+        let mut recurrence_id = None;
+        let mut cancelled = false;
 
         for prop in &event.properties {
             match prop.name.as_str() {
@@ -78,85 +494,595 @@ impl CalendarService {
                 "SUMMARY" => summary = prop.value.clone(),
                 "DESCRIPTION" => description = prop.value.clone(),
                 "LOCATION" => location = prop.value.clone(),
+                "URL" => url = prop.value.clone(),
+                "X-GOOGLE-CONFERENCE" => google_conference = prop.value.clone(),
+                "X-MICROSOFT-ONLINEMEETINGURL" | "X-MICROSOFT-SKYPETEAMSMEETINGURL" => {
+                    microsoft_meeting = microsoft_meeting.or_else(|| prop.value.clone())
+                }
+                "ORGANIZER" => organizer = parse_attendee(prop),
+                "ATTENDEE" => attendees.extend(parse_attendee(prop)),
+                "X-MICROSOFT-CDO-BUSYSTATUS" | "X-MICROSOFT-CDO-INTENDEDSTATUS" => {
+                    busy_status_oof |= prop.value.as_deref().is_some_and(|s| s.eq_ignore_ascii_case("OOF"))
+                }
                 "DTSTART" => {
                     if let Some(ref value) = prop.value {
                         // Check if it's an all-day event (DATE vs DATETIME)
-                        let is_date_only = prop.params.as_ref()
-                            .and_then(|p| p.iter().find(|(k, _)| k == "VALUE"))
-                            .map(|(_, v)| v.iter().any(|v| v == "DATE"))
-                            .unwrap_or(value.len() == 8);
-
+                        let is_date_only = self.is_date_only(prop, value);
                         all_day = is_date_only;
-                        dtstart = self.parse_datetime(value, is_date_only);
+                        dtstart = self.parse_datetime(value, is_date_only, self.zone_for(prop, timezones));
                     }
                 }
                 "DTEND" => {
                     if let Some(ref value) = prop.value {
-                        let is_date_only = prop.params.as_ref()
-                            .and_then(|p| p.iter().find(|(k, _)| k == "VALUE"))
-                            .map(|(_, v)| v.iter().any(|v| v == "DATE"))
-                            .unwrap_or(value.len() == 8);
-
-                        dtend = self.parse_datetime(value, is_date_only);
+                        let is_date_only = self.is_date_only(prop, value);
+                        dtend = self.parse_datetime(value, is_date_only, self.zone_for(prop, timezones));
+                    }
+                }
+                "DURATION" => duration = prop.value.as_deref().and_then(parse_duration),
+                "RRULE" => rrule = prop.value.as_deref().and_then(RecurrenceRule::parse),
+                "RDATE" => rdates.extend(self.parse_datetime_list(prop, timezones)),
+                "EXDATE" => exdates.extend(self.parse_datetime_list(prop, timezones)),
+                "RECURRENCE-ID" => {
+                    if let Some(ref value) = prop.value {
+                        let is_date_only = self.is_date_only(prop, value);
+                        recurrence_id = self.parse_datetime(value, is_date_only, self.zone_for(prop, timezones));
                     }
                 }
+                "STATUS" => {
+                    cancelled = prop.value.as_deref().is_some_and(|s| s.eq_ignore_ascii_case("CANCELLED"))
+                }
                 _ => {}
             }
         }
 
-        let uid = uid?;
-        let summary = summary.unwrap_or_else(|| "(No title)".to_string());
-        let start_time = dtstart?;
-        let end_time = dtend.unwrap_or(start_time + 3600); // Default to 1 hour
+        // Dedicated conference properties win over links mentioned in free text
+        let conference = detect_conference(
+            [&google_conference, &microsoft_meeting, &url, &location, &description]
+                .into_iter()
+                .filter_map(|text| text.as_deref()),
+        );
+
+        let out_of_office = busy_status_oof || summary.as_deref().is_some_and(looks_out_of_office);
 
-        // Filter to today's events only
-        let start_date = DateTime::from_timestamp(start_time, 0)?.date_naive();
-        let end_date = DateTime::from_timestamp(end_time, 0)?.date_naive();
+        Some(RawEvent {
+            uid: uid?,
+            summary,
+            description,
+            location,
+            url,
+            conference,
+            organizer,
+            attendees,
+            out_of_office,
+            alarms: event.alarms.iter().filter_map(|alarm| self.parse_alarm(alarm)).collect(),
+            start: dtstart?,
+            end: dtend,
+            duration,
+            all_day,
+            rrule,
+            rdates,
+            exdates,
+            recurrence_id,
+            cancelled,
+        })
+    }
 
-        // Include if event overlaps with today
-        if start_date > tomorrow || end_date < today {
+    // EMAIL alarms are for the server to send, so only DISPLAY/AUDIO ones become reminders
+    fn parse_alarm(&self, alarm: &IcalAlarm) -> Option<AlarmTrigger> {
+        let action = alarm.properties.iter().find(|p| p.name == "ACTION").and_then(|p| p.value.as_deref());
+        if action.is_some_and(|a| a.eq_ignore_ascii_case("EMAIL")) {
             return None;
         }
 
+        let trigger = alarm.properties.iter().find(|p| p.name == "TRIGGER")?;
+        let value = trigger.value.as_deref()?.trim();
+
+        if param(trigger, "VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE-TIME")) {
+            return Some(AlarmTrigger::Absolute(self.parse_datetime(value, false, None)?.to_timestamp()));
+        }
+
+        let offset = parse_duration(value)?;
+        match param(trigger, "RELATED") {
+            Some(related) if related.eq_ignore_ascii_case("END") => Some(AlarmTrigger::End(offset)),
+            _ => Some(AlarmTrigger::Start(offset)),
+        }
+    }
+
+    // Turns a master event (plus its RECURRENCE-ID overrides) into the concrete
+    // instances that overlap [window_start, window_end)
+    fn expand_event(
+        &self,
+        raw: &RawEvent,
+        overrides: &[RawEvent],
+        window_start: i64,
+        window_end: i64,
+    ) -> Vec<CalendarEvent> {
+        let mut instances = Vec::new();
+
+        let start_time = raw.start.to_timestamp();
+        let length = self.event_length(raw, start_time);
+        let recurring = raw.rrule.is_some() || !raw.rdates.is_empty();
+
+        // Single events and standalone overrides
+        if !recurring {
+            if raw.cancelled || start_time >= window_end || start_time + length <= window_start {
+                return instances;
+            }
+            let id = match raw.recurrence_id.as_ref().map(|r| r.to_timestamp()) {
+                Some(original) => format!("{}_{}", raw.uid, original),
+                None => raw.uid.clone(),
+            };
+            instances.push(self.build_event(raw, id, start_time, start_time + length));
+            return instances;
+        }
+
+        if raw.cancelled {
+            return instances;
+        }
+
+        let mut starts: Vec<i64> = match raw.rrule {
+            Some(ref rule) => rule
+                .expand(raw.start.value, |dt| raw.start.zone.to_timestamp(dt), window_end)
+                .into_iter()
+                .map(|dt| raw.start.zone.to_timestamp(dt))
+                .collect(),
+            None => vec![start_time],
+        };
+        starts.extend(raw.rdates.iter().map(|d| d.to_timestamp()));
+        starts.sort_unstable();
+        starts.dedup();
+
+        let excluded: Vec<i64> = raw.exdates.iter().map(|d| d.to_timestamp()).collect();
+        let overridden: HashMap<i64, &RawEvent> = overrides
+            .iter()
+            .filter_map(|o| Some((o.recurrence_id.as_ref()?.to_timestamp(), o)))
+            .collect();
+
+        for start in starts {
+            if excluded.contains(&start) || overridden.contains_key(&start) {
+                continue;
+            }
+            if start >= window_end || start + length <= window_start {
+                continue;
+            }
+            instances.push(self.build_event(raw, format!("{}_{}", raw.uid, start), start, start + length));
+        }
+
+        // Moved or edited instances, which may land inside the window even if the original did not
+        for (original, instance) in overridden {
+            if instance.cancelled || excluded.contains(&original) {
+                continue;
+            }
+            let start = instance.start.to_timestamp();
+            let end = start + self.event_length(instance, start);
+            if start >= window_end || end <= window_start {
+                continue;
+            }
+            instances.push(self.build_event(instance, format!("{}_{}", raw.uid, original), start, end));
+        }
+
+        instances
+    }
+
+    fn build_event(&self, raw: &RawEvent, id: String, start_time: i64, end_time: i64) -> CalendarEvent {
         let now = Utc::now().timestamp();
         let is_now = start_time <= now && end_time > now;
         let is_soon = !is_now && start_time > now && start_time <= now + 1800;
 
-        Some(CalendarEvent {
-            id: uid,
-            summary,
-            description,
-            location,
+        CalendarEvent {
+            id,
+            source: String::new(),
+            summary: raw.summary.clone().unwrap_or_else(|| "(No title)".to_string()),
+            description: raw.description.clone(),
+            location: raw.location.clone(),
             start_time,
             end_time,
-            all_day,
-            html_link: None,
+            all_day: raw.all_day,
+            html_link: raw.url.clone(),
+            join_url: raw.conference.as_ref().map(|c| c.join_url.clone()),
+            conference_provider: raw.conference.as_ref().map(|c| c.provider.clone()),
+            organizer: raw.organizer.clone(),
+            attendees: raw.attendees.clone(),
+            my_status: None,
+            out_of_office: raw.out_of_office,
+            reminder_offsets: reminder_offsets(&raw.alarms, start_time, end_time),
+            color: None,
             is_now,
             is_soon,
-        })
+        }
+    }
+
+    fn event_length(&self, raw: &RawEvent, start_time: i64) -> i64 {
+        raw.end
+            .as_ref()
+            .map(|end| end.to_timestamp() - start_time)
+            .or(raw.duration)
+            .unwrap_or(if raw.all_day { 86400 } else { 3600 }) // Default to 1 day / 1 hour
+    }
+
+    fn is_date_only(&self, prop: &Property, value: &str) -> bool {
+        prop.params.as_ref()
+            .and_then(|p| p.iter().find(|(k, _)| k == "VALUE"))
+            .map(|(_, v)| v.iter().any(|v| v == "DATE"))
+            .unwrap_or(value.len() == 8)
+    }
+
+    // RDATE/EXDATE may carry several comma-separated values; PERIOD values keep only their start
+    fn parse_datetime_list(&self, prop: &Property, timezones: &TimeZoneResolver) -> Vec<IcsDateTime> {
+        let value = match prop.value {
+            Some(ref value) => value,
+            None => return Vec::new(),
+        };
+        let zone = self.zone_for(prop, timezones);
+
+        value
+            .split(',')
+            .filter_map(|item| {
+                let item = item.split('/').next()?.trim();
+                let is_date_only = self.is_date_only(prop, item);
+                self.parse_datetime(item, is_date_only, zone.clone())
+            })
+            .collect()
+    }
+
+    // Zone from the property's TZID parameter, if it names one we can resolve
+    fn zone_for(&self, prop: &Property, timezones: &TimeZoneResolver) -> Option<ResolvedZone> {
+        prop.params.as_ref()
+            .and_then(|p| p.iter().find(|(k, _)| k == "TZID"))
+            .and_then(|(_, v)| v.first())
+            .and_then(|tzid| timezones.resolve(tzid))
     }
 
-    fn parse_datetime(&self, value: &str, is_date_only: bool) -> Option<i64> {
+    fn user_zone(&self) -> IcsZone {
+        IcsZone::Zone(ResolvedZone::Iana(self.timezone))
+    }
+
+    fn parse_datetime(&self, value: &str, is_date_only: bool, tz: Option<ResolvedZone>) -> Option<IcsDateTime> {
         if is_date_only {
-            // Format: YYYYMMDD
+            // Format: YYYYMMDD, a floating date that starts at midnight in the user's timezone
             let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
-            let datetime = date.and_hms_opt(0, 0, 0)?;
-            Some(datetime.and_utc().timestamp())
+            Some(IcsDateTime {
+                value: date.and_hms_opt(0, 0, 0)?,
+                zone: self.user_zone(),
+            })
         } else {
             // Format: YYYYMMDDTHHMMSS or YYYYMMDDTHHMMSSZ
-            let value = value.trim_end_matches('Z');
-            let datetime = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+            // UTC if it ends with Z, the TZID zone if one was given, otherwise floating in the user's timezone
+            let zone = match tz {
+                _ if value.ends_with('Z') => IcsZone::Utc,
+                Some(tz) => IcsZone::Zone(tz),
+                None => self.user_zone(),
+            };
+            let datetime = NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S").ok()?;
+            Some(IcsDateTime { value: datetime, zone })
+        }
+    }
+}
+
+// The user's PARTSTAT on the event, matched case-insensitively against their addresses.
+// Organizers without their own ATTENDEE line have implicitly accepted.
+pub fn participation_status(event: &CalendarEvent, my_emails: &[String]) -> Option<String> {
+    let is_me = |email: &str| my_emails.iter().any(|mine| mine.trim().eq_ignore_ascii_case(email));
+
+    event
+        .attendees
+        .iter()
+        .find(|a| is_me(&a.email))
+        .map(|a| a.status.clone().unwrap_or_else(|| "needs_action".to_string()))
+        .or_else(|| {
+            event
+                .organizer
+                .as_ref()
+                .filter(|o| is_me(&o.email))
+                .map(|_| "accepted".to_string())
+        })
+}
+
+// Converts alarm triggers into lead times before this instance's start
+fn reminder_offsets(alarms: &[AlarmTrigger], start_time: i64, end_time: i64) -> Vec<i64> {
+    let mut offsets: Vec<i64> = alarms
+        .iter()
+        .filter_map(|alarm| match *alarm {
+            AlarmTrigger::Start(offset) => Some(-offset),
+            AlarmTrigger::End(offset) => Some(start_time - end_time - offset),
+            // An absolute trigger belongs to one occurrence; ignore it on instances far away from it
+            AlarmTrigger::Absolute(at) => Some(start_time - at).filter(|lead| lead.abs() <= 86400),
+        })
+        .collect();
+    offsets.sort_unstable();
+    offsets.dedup();
+    offsets
+}
+
+// Timed tasks are overdue once their due time passes, date-only ones once their day has ended
+pub fn is_task_overdue(due_time: Option<i64>, due_all_day: bool, now: i64, today_start: i64) -> bool {
+    match due_time {
+        Some(due) if due_all_day => due < today_start,
+        Some(due) => due < now,
+        None => false,
+    }
+}
+
+// Calendars without Outlook's busy status only mark absences in the title
+fn looks_out_of_office(summary: &str) -> bool {
+    const PHRASES: [&str; 6] = ["out of office", "ooo", "pto", "vacation", "on leave", "annual leave"];
 
-            // Assume UTC if ends with Z, otherwise local time
-            if value.ends_with('Z') {
-                Some(datetime.and_utc().timestamp())
-            } else {
-                // Convert local time to UTC
-                let local = Local::now().timezone();
-                local.from_local_datetime(&datetime)
-                    .earliest()
-                    .map(|dt| dt.timestamp())
+    let words: Vec<String> = summary
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let normalized = format!(" {} ", words.join(" "));
+
+    PHRASES.iter().any(|phrase| normalized.contains(&format!(" {} ", phrase)))
+}
+
+// ORGANIZER/ATTENDEE values are cal-address URIs, normally mailto:
+fn parse_attendee(prop: &Property) -> Option<EventAttendee> {
+    let value = prop.value.as_deref()?.trim();
+    let email = match value.get(..7) {
+        Some(scheme) if scheme.eq_ignore_ascii_case("mailto:") => &value[7..],
+        _ => value,
+    };
+    if email.is_empty() {
+        return None;
+    }
+
+    Some(EventAttendee {
+        email: email.to_string(),
+        name: param(prop, "CN").map(|cn| cn.trim_matches('"').to_string()),
+        role: param(prop, "ROLE").map(normalize_enum),
+        status: param(prop, "PARTSTAT").map(normalize_enum),
+    })
+}
+
+fn param<'a>(prop: &'a Property, name: &str) -> Option<&'a str> {
+    prop.params.as_ref()?
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        .and_then(|(_, v)| v.first())
+        .map(String::as_str)
+}
+
+// webcal:// is just a hint to open the link in a calendar app; the feed itself is served over HTTPS
+fn normalize_feed_url(url: &str) -> String {
+    let url = url.trim();
+    match url.split_once("://") {
+        Some((scheme, rest)) if scheme.eq_ignore_ascii_case("webcal") || scheme.eq_ignore_ascii_case("webcals") => {
+            format!("https://{}", rest)
+        }
+        _ => url.to_string(),
+    }
+}
+
+// Walks a vdir tree for .ics files; dotfiles are skipped since sync tools use them for
+// temporary copies and their own state
+fn collect_ics_files(
+    directory: &Path,
+    visited: &mut HashSet<PathBuf>,
+    files: &mut Vec<(PathBuf, fs::Metadata)>,
+) -> Result<()> {
+    // A symlink back up the tree (e.g. `a/link -> ..`) would otherwise recurse forever
+    if !visited.insert(fs::canonicalize(directory)?) {
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.file_name().and_then(|n| n.to_str()).is_none_or(|n| n.starts_with('.')) {
+            continue;
+        }
+
+        // fs::metadata follows symlinks, which vdir collections are sometimes made of; entries
+        // that vanish mid-sync or dangle are skipped
+        let Ok(metadata) = fs::metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            collect_ics_files(&path, visited, files)?;
+        } else if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("ics")) {
+            files.push((path, metadata));
+        }
+    }
+    Ok(())
+}
+
+// NEEDS-ACTION -> needs_action, matching how enum-like values are stored elsewhere
+fn normalize_enum(value: &str) -> String {
+    value.to_ascii_lowercase().replace('-', "_")
+}
+
+// Sort: all-day events first, then by start time
+pub fn sort_events(events: &mut [CalendarEvent]) {
+    events.sort_by(|a, b| {
+        match (a.all_day, b.all_day) {
+            (true, false) => std::cmp::Ordering::Less,
+            (false, true) => std::cmp::Ordering::Greater,
+            _ => a.start_time.cmp(&b.start_time),
+        }
+    });
+}
+
+// Parses an RFC 5545 DURATION such as PT1H30M, P1D or -PT15M into seconds
+pub(crate) fn parse_duration(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (sign, rest) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.trim_start_matches('+')),
+    };
+    let rest = rest.strip_prefix('P')?;
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+
+    for c in rest.chars() {
+        match c {
+            'T' => in_time = true,
+            '0'..='9' => number.push(c),
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match (unit, in_time) {
+                    ('W', false) => Duration::weeks(n),
+                    ('D', false) => Duration::days(n),
+                    ('H', true) => Duration::hours(n),
+                    ('M', true) => Duration::minutes(n),
+                    ('S', true) => Duration::seconds(n),
+                    _ => return None,
+                };
             }
         }
     }
+
+    if !number.is_empty() {
+        return None;
+    }
+    Some(sign * total.num_seconds())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A daily 10:00 UTC meeting that started three days ago, with `extra` lines appended to the
+    // calendar. Dates are relative to today because parsing expands into the brief's window.
+    fn daily_meeting(extra: &str) -> CalendarData {
+        let day = |offset: i64| (Utc::now().date_naive() + Duration::days(offset)).format("%Y%m%d").to_string();
+        let extra = extra
+            .replace("{tomorrow}", &day(1))
+            .replace("{in_two_days}", &day(2));
+        let ics = format!(
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:standup\r\nSUMMARY:Standup\r\nDTSTART:{}T100000Z\r\n\
+             DURATION:PT15M\r\nRRULE:FREQ=DAILY\r\n{}END:VCALENDAR\r\n",
+            day(-3),
+            extra
+        );
+        CalendarService::new(Tz::UTC).parse_ics_content(&ics).unwrap()
+    }
+
+    fn instance_days(data: &CalendarData) -> Vec<NaiveDate> {
+        data.events
+            .iter()
+            .map(|e| chrono::DateTime::from_timestamp(e.start_time, 0).unwrap().date_naive())
+            .collect()
+    }
+
+    #[test]
+    fn expands_into_the_brief_window() {
+        let data = daily_meeting("END:VEVENT\r\n");
+        assert_eq!(data.events.len(), BRIEF_WINDOW_DAYS as usize);
+        assert_eq!(instance_days(&data)[0], Utc::now().date_naive());
+    }
+
+    #[test]
+    fn exdate_removes_an_instance() {
+        let data = daily_meeting("EXDATE:{tomorrow}T100000Z\r\nEND:VEVENT\r\n");
+        let tomorrow = Utc::now().date_naive() + Duration::days(1);
+
+        assert_eq!(data.events.len(), BRIEF_WINDOW_DAYS as usize - 1);
+        assert!(!instance_days(&data).contains(&tomorrow));
+    }
+
+    #[test]
+    fn recurrence_id_overrides_an_instance() {
+        let data = daily_meeting(
+            "END:VEVENT\r\nBEGIN:VEVENT\r\nUID:standup\r\nSUMMARY:Standup (moved)\r\n\
+             RECURRENCE-ID:{in_two_days}T100000Z\r\nDTSTART:{in_two_days}T150000Z\r\nDURATION:PT30M\r\nEND:VEVENT\r\n",
+        );
+
+        assert_eq!(data.events.len(), BRIEF_WINDOW_DAYS as usize);
+        let moved: Vec<&CalendarEvent> = data.events.iter().filter(|e| e.summary == "Standup (moved)").collect();
+        assert_eq!(moved.len(), 1);
+        assert_eq!(moved[0].end_time - moved[0].start_time, 1800);
+
+        let original = (Utc::now().date_naive() + Duration::days(2)).and_hms_opt(10, 0, 0).unwrap();
+        let original = original.and_utc().timestamp();
+        assert_eq!(moved[0].id, format!("standup_{}", original));
+        assert!(data.events.iter().all(|e| e.start_time != original));
+    }
+
+    // A scratch vdir under the temp directory, removed again when dropped
+    struct TempVdir(PathBuf);
+
+    impl TempVdir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("wtftoday-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+
+        fn write(&self, file: &str, uid: &str) {
+            let today = Utc::now().date_naive().format("%Y%m%d");
+            let path = self.0.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(
+                path,
+                format!(
+                    "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:{}\r\nSUMMARY:{}\r\nDTSTART:{}T100000Z\r\n\
+                     DURATION:PT30M\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+                    uid, uid, today
+                ),
+            )
+            .unwrap();
+        }
+    }
+
+    impl Drop for TempVdir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn survives_symlink_cycles_in_a_vdir() {
+        let vdir = TempVdir::new("symlink-cycle");
+        vdir.write("work/standup.ics", "standup");
+        std::os::unix::fs::symlink("..", vdir.0.join("work/loop")).unwrap();
+        std::os::unix::fs::symlink(&vdir.0, vdir.0.join("work/root")).unwrap();
+
+        let data = CalendarService::new(Tz::UTC)
+            .parse_from_directory(vdir.0.to_str().unwrap())
+            .unwrap();
+        let uids: Vec<&str> = data.events.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(uids, vec!["standup"]);
+    }
+
+    #[test]
+    fn reuses_parsed_vdir_files_for_any_window() {
+        let vdir = TempVdir::new("window-cache");
+        let file = vdir.0.join("standup.ics");
+        let today = Utc::now().date_naive();
+        fs::write(
+            &file,
+            format!(
+                "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:standup\r\nSUMMARY:Old\r\nDTSTART:{}T100000Z\r\n\
+                 DURATION:PT15M\r\nRRULE:FREQ=DAILY\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+                today.format("%Y%m%d")
+            ),
+        )
+        .unwrap();
+        let path = vdir.0.to_str().unwrap();
+        let modified = fs::metadata(&file).unwrap().modified().unwrap();
+
+        let brief = CalendarService::new(Tz::UTC);
+        assert_eq!(brief.parse_from_directory(path).unwrap().events.len(), BRIEF_WINDOW_DAYS as usize);
+
+        // Same size and mtime: a cached file is expanded again rather than re-read
+        let content = fs::read_to_string(&file).unwrap().replace("SUMMARY:Old", "SUMMARY:New");
+        fs::write(&file, content).unwrap();
+        fs::File::options().write(true).open(&file).unwrap().set_modified(modified).unwrap();
+
+        let start = start_of_day(Tz::UTC, today + Duration::days(30));
+        let later = CalendarService::with_window(Tz::UTC, start, start + 2 * 86400);
+        let events = later.parse_from_directory(path).unwrap().events;
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| e.summary == "Old" && e.start_time >= start));
+
+        let events = brief.parse_from_directory(path).unwrap().events;
+        assert_eq!(events.len(), BRIEF_WINDOW_DAYS as usize);
+        assert_eq!(events[0].summary, "Old");
+    }
 }
@@ -0,0 +1,154 @@
+use reqwest::Url;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conference {
+    pub join_url: String,
+    pub provider: String, // "zoom", "google_meet", "teams", "jitsi", "webex"
+}
+
+/// Returns the first video-conference link found in `texts`, which are searched in order,
+/// so callers should pass the most specific properties first.
+pub fn detect_conference<'a>(texts: impl IntoIterator<Item = &'a str>) -> Option<Conference> {
+    texts.into_iter().flat_map(extract_urls).find_map(|url| {
+        let parsed = Url::parse(&url).ok()?;
+        let provider = provider_for(&parsed)?;
+        Some(Conference {
+            join_url: url,
+            provider: provider.to_string(),
+        })
+    })
+}
+
+fn provider_for(url: &Url) -> Option<&'static str> {
+    let host = url.host_str()?.to_ascii_lowercase();
+    let path = url.path();
+    let on = |domain: &str| host == domain || host.ends_with(&format!(".{}", domain));
+
+    if (on("zoom.us") || on("zoomgov.com"))
+        && ["/j/", "/my/", "/w/", "/s/", "/wc/"].iter().any(|p| path.starts_with(p))
+    {
+        return Some("zoom");
+    }
+    if host == "meet.google.com" && path.len() > 1 && !path.starts_with("/_meet") {
+        return Some("google_meet");
+    }
+    if (host == "teams.microsoft.com" && path.starts_with("/l/meetup-join/"))
+        || (host == "teams.live.com" && path.starts_with("/meet/"))
+    {
+        return Some("teams");
+    }
+    if (host == "meet.jit.si" || host == "8x8.vc") && path.len() > 1 {
+        return Some("jitsi");
+    }
+    if on("webex.com") && ["/meet/", "/join/", "/j.php", "/wbxmjs/"].iter().any(|p| path.contains(p)) {
+        return Some("webex");
+    }
+
+    None
+}
+
+// Entities that end a link in HTML-escaped descriptions, where quotes and brackets arrive encoded
+const ENTITY_DELIMITERS: &[&str] = &["&quot;", "&#34;", "&#39;", "&apos;", "&lt;", "&gt;"];
+
+// Pulls http(s) links out of free text. Stops at characters that end a link in plain text,
+// HTML attributes and unescaped iCalendar text (`\n`, `\,`), and decodes `&amp;` in the query.
+fn extract_urls(text: &str) -> Vec<String> {
+    let mut urls = Vec::new();
+    let mut rest = text;
+
+    while let Some(pos) = rest.find("http") {
+        let candidate = &rest[pos..];
+        if !(candidate.starts_with("https://") || candidate.starts_with("http://")) {
+            rest = &rest[pos + 4..];
+            continue;
+        }
+
+        let end = candidate
+            .find(|c: char| c.is_whitespace() || "<>\"'()[]{}\\|^`".contains(c))
+            .unwrap_or(candidate.len());
+        let end = ENTITY_DELIMITERS
+            .iter()
+            .filter_map(|entity| candidate[..end].find(entity))
+            .fold(end, usize::min);
+        let url = candidate[..end].trim_end_matches(['.', ',', ';', ':', '!', '?']);
+        urls.push(url.replace("&amp;", "&"));
+
+        rest = &candidate[end..];
+    }
+
+    urls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider(url: &str) -> Option<&'static str> {
+        provider_for(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn trims_trailing_punctuation() {
+        assert_eq!(
+            extract_urls("Join: https://zoom.us/j/123456789. Or dial in, https://meet.google.com/abc-defg-hij!"),
+            vec!["https://zoom.us/j/123456789", "https://meet.google.com/abc-defg-hij"]
+        );
+        assert_eq!(extract_urls("(see https://meet.jit.si/standup)"), vec!["https://meet.jit.si/standup"]);
+    }
+
+    #[test]
+    fn stops_at_angle_brackets_and_ical_escapes() {
+        assert_eq!(extract_urls("Zoom <https://zoom.us/j/42>"), vec!["https://zoom.us/j/42"]);
+        assert_eq!(
+            extract_urls("Link: https://zoom.us/j/42\\nPasscode: 1\\, https://example.com/x"),
+            vec!["https://zoom.us/j/42", "https://example.com/x"]
+        );
+        assert_eq!(extract_urls("no links, just httpd and http:/broken"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn reads_html_escaped_descriptions() {
+        let description = "&lt;a href=&quot;https://us02web.zoom.us/j/851?pwd=abc&amp;uname=x&quot;&gt;Join&lt;/a&gt;";
+        assert_eq!(extract_urls(description), vec!["https://us02web.zoom.us/j/851?pwd=abc&uname=x"]);
+
+        let html = r#"<a href="https://teams.microsoft.com/l/meetup-join/19%3ameeting_x%40thread.v2/0">Join</a>"#;
+        assert_eq!(
+            extract_urls(html),
+            vec!["https://teams.microsoft.com/l/meetup-join/19%3ameeting_x%40thread.v2/0"]
+        );
+    }
+
+    #[test]
+    fn matches_zoom_subdomains_only_on_zoom() {
+        assert_eq!(provider("https://zoom.us/j/123"), Some("zoom"));
+        assert_eq!(provider("https://acme.zoom.us/my/alice"), Some("zoom"));
+        assert_eq!(provider("https://acme.zoomgov.com/j/123"), Some("zoom"));
+        assert_eq!(provider("https://notzoom.us/j/123"), None);
+        assert_eq!(provider("https://zoom.us.evil.com/j/123"), None);
+        // Not a meeting link
+        assert_eq!(provider("https://zoom.us/pricing"), None);
+    }
+
+    #[test]
+    fn matches_meet_and_teams_hosts() {
+        assert_eq!(provider("https://meet.google.com/abc-defg-hij"), Some("google_meet"));
+        assert_eq!(provider("https://meet.google.com/"), None);
+        assert_eq!(provider("https://meet.google.com/_meet/whoami"), None);
+        assert_eq!(provider("https://teams.microsoft.com/l/meetup-join/19%3a/0"), Some("teams"));
+        assert_eq!(provider("https://teams.live.com/meet/9312"), Some("teams"));
+        assert_eq!(provider("https://teams.microsoft.com/l/channel/19%3a"), None);
+        assert_eq!(provider("https://fake.teams.microsoft.com/l/meetup-join/19%3a/0"), None);
+    }
+
+    #[test]
+    fn detects_the_first_conference_link_in_order() {
+        let conference = detect_conference([
+            "https://example.com/agenda",
+            "Join https://acme.webex.com/meet/alice or https://zoom.us/j/1",
+        ])
+        .unwrap();
+        assert_eq!(conference.provider, "webex");
+        assert_eq!(conference.join_url, "https://acme.webex.com/meet/alice");
+        assert!(detect_conference(["https://example.com"]).is_none());
+    }
+}
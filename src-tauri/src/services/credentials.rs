@@ -3,6 +3,16 @@ use crate::error::{AppError, Result};
 
 const SERVICE_NAME: &str = "wtftoday";
 
+// Keyring entry holding the secret for a calendar source (CalDAV password, feed password or token)
+pub fn calendar_credential_key(source_id: &str) -> String {
+    format!("calendar_source_{}", source_id)
+}
+
+// Keyring entry holding a GitHub account's token
+pub fn github_credential_key(account_id: &str) -> String {
+    format!("github_account_{}", account_id)
+}
+
 pub fn save_credential(key: &str, value: &str) -> Result<()> {
     let entry = Entry::new(SERVICE_NAME, key)?;
     entry.set_password(value)?;
@@ -0,0 +1,216 @@
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, USER_AGENT};
+use crate::error::{AppError, Result};
+use crate::models::*;
+
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+// Where a device-flow sign-in stands after one poll
+pub enum DeviceFlowPoll {
+    Pending,
+    // GitHub wants to be polled less often, every this many seconds
+    SlowDown(i64),
+    Granted(GitHubOAuthToken),
+}
+
+/// Signs in to GitHub with the OAuth device flow, which needs only the app's client ID. The
+/// endpoints live on the web host (github.com or the Enterprise Server), not the API.
+pub struct GitHubOAuth {
+    client: reqwest::Client,
+    web_url: String,
+    client_id: String,
+}
+
+impl GitHubOAuth {
+    pub fn new(endpoints: &GitHubEndpoints, client_id: &str) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+        // The token endpoints answer form-encoded unless asked for JSON
+        headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+        headers.insert(USER_AGENT, HeaderValue::from_static("WTFToday/1.0"));
+
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()?;
+
+        Ok(Self {
+            client,
+            web_url: endpoints.web_url(),
+            client_id: client_id.to_string(),
+        })
+    }
+
+    pub async fn request_device_code(&self) -> Result<GitHubDeviceCode> {
+        let response = self
+            .client
+            .post(format!("{}/login/device/code", self.web_url))
+            .form(&[("client_id", self.client_id.as_str()), ("scope", GITHUB_OAUTH_SCOPES)])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(oauth_error(response).await);
+        }
+
+        // Device flow turned off for the app comes back as 200 with an error body
+        let body: serde_json::Value = response.json().await?;
+        if let Some(error) = body.get("error").and_then(|e| e.as_str()) {
+            let description = body.get("error_description").and_then(|d| d.as_str()).unwrap_or(error);
+            return Err(AppError::Validation(format!("GitHub sign-in failed: {}", description)));
+        }
+        serde_json::from_value(body).map_err(|e| AppError::Parse(e.to_string()))
+    }
+
+    pub async fn poll_device_token(&self, device_code: &str) -> Result<DeviceFlowPoll> {
+        let token = self
+            .request_token(&[("device_code", device_code), ("grant_type", DEVICE_CODE_GRANT)])
+            .await?;
+
+        match token.error.as_deref() {
+            None => Ok(DeviceFlowPoll::Granted(token)),
+            Some("authorization_pending") => Ok(DeviceFlowPoll::Pending),
+            Some("slow_down") => Ok(DeviceFlowPoll::SlowDown(token.interval.unwrap_or(10))),
+            Some("expired_token") => Err(AppError::Validation(
+                "The sign-in code expired, start signing in again".to_string(),
+            )),
+            Some("access_denied") => Err(AppError::Validation("Sign-in was cancelled on GitHub".to_string())),
+            Some(error) => Err(token_error(error, &token)),
+        }
+    }
+
+    // Posts to the token endpoint as the app, checking that a granted token is there
+    async fn request_token(&self, params: &[(&str, &str)]) -> Result<GitHubOAuthToken> {
+        let mut form = vec![("client_id", self.client_id.as_str())];
+        form.extend_from_slice(params);
+
+        let response = self
+            .client
+            .post(format!("{}/login/oauth/access_token", self.web_url))
+            .form(&form)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(oauth_error(response).await);
+        }
+
+        let token: GitHubOAuthToken = response.json().await?;
+        if token.error.is_none() && token.access_token.is_none() {
+            return Err(AppError::Parse("GitHub returned no access token".to_string()));
+        }
+        Ok(token)
+    }
+}
+
+// A rejected grant such as a disabled device flow
+fn token_error(error: &str, token: &GitHubOAuthToken) -> AppError {
+    AppError::Validation(format!(
+        "GitHub sign-in failed: {}",
+        token.error_description.as_deref().unwrap_or(error)
+    ))
+}
+
+async fn oauth_error(response: reqwest::Response) -> AppError {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    AppError::Other(format!("GitHub sign-in error ({}): {}", status, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    // Answers every token request with `reply`, recording the forms it was sent
+    async fn token_endpoint(reply: &'static str) -> (GitHubOAuth, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoints = GitHubEndpoints::for_api_url(&format!("http://{}", listener.local_addr().unwrap()));
+        let forms = Arc::new(Mutex::new(Vec::new()));
+        let log = forms.clone();
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let log = log.clone();
+                tokio::spawn(async move {
+                    let mut stream = BufReader::new(stream);
+                    let mut line = String::new();
+                    stream.read_line(&mut line).await.unwrap();
+                    assert!(line.starts_with("POST /login/oauth/access_token "), "{}", line);
+
+                    let mut length = 0;
+                    loop {
+                        line.clear();
+                        stream.read_line(&mut line).await.unwrap();
+                        let Some((name, value)) = line.trim_end().split_once(':') else { break };
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                    let mut form = vec![0; length];
+                    stream.read_exact(&mut form).await.unwrap();
+                    log.lock().unwrap().push(String::from_utf8(form).unwrap());
+
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        reply.len(),
+                        reply
+                    );
+                    stream.get_mut().write_all(response.as_bytes()).await.unwrap();
+                });
+            }
+        });
+
+        (GitHubOAuth::new(&endpoints, "Iv1.test").unwrap(), forms)
+    }
+
+    #[tokio::test]
+    async fn keeps_polling_while_authorization_is_pending() {
+        let (oauth, forms) = token_endpoint(r#"{"error":"authorization_pending","error_description":"The authorization request is still pending."}"#).await;
+
+        assert!(matches!(oauth.poll_device_token("dev-123").await, Ok(DeviceFlowPoll::Pending)));
+        // Only the client ID identifies the app; the device flow sends no secret
+        assert_eq!(
+            forms.lock().unwrap().as_slice(),
+            ["client_id=Iv1.test&device_code=dev-123&grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Adevice_code"]
+        );
+    }
+
+    #[tokio::test]
+    async fn slows_down_to_the_interval_github_asks_for() {
+        let (oauth, _) = token_endpoint(r#"{"error":"slow_down","error_description":"Too many requests.","interval":10}"#).await;
+        assert!(matches!(oauth.poll_device_token("dev-123").await, Ok(DeviceFlowPoll::SlowDown(10))));
+    }
+
+    #[tokio::test]
+    async fn fails_once_the_code_expired_or_was_denied() {
+        let (oauth, _) = token_endpoint(r#"{"error":"expired_token","error_description":"The device code has expired."}"#).await;
+        match oauth.poll_device_token("dev-123").await {
+            Err(AppError::Validation(message)) => assert!(message.contains("expired"), "{}", message),
+            _ => panic!("an expired code should end the sign-in"),
+        }
+
+        let (oauth, _) = token_endpoint(r#"{"error":"access_denied","error_description":"The user has denied your application access."}"#).await;
+        match oauth.poll_device_token("dev-123").await {
+            Err(AppError::Validation(message)) => assert!(message.contains("cancelled"), "{}", message),
+            _ => panic!("a denied sign-in should end the sign-in"),
+        }
+    }
+
+    #[tokio::test]
+    async fn returns_the_granted_token() {
+        let (oauth, _) = token_endpoint(r#"{"access_token":"gho_abc","token_type":"bearer","scope":"notifications,read:user,repo"}"#).await;
+        match oauth.poll_device_token("dev-123").await {
+            Ok(DeviceFlowPoll::Granted(token)) => {
+                assert_eq!(token.access_token.as_deref(), Some("gho_abc"));
+                assert_eq!(token.expires_in, None);
+            }
+            _ => panic!("the token should be granted"),
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_a_grant_without_a_token() {
+        let (oauth, _) = token_endpoint(r#"{"token_type":"bearer"}"#).await;
+        assert!(matches!(oauth.poll_device_token("dev-123").await, Err(AppError::Parse(_))));
+    }
+}
@@ -1,12 +1,109 @@
-use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, USER_AGENT};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use reqwest::header::{
+    HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, IF_MODIFIED_SINCE, LAST_MODIFIED, LINK, RETRY_AFTER, USER_AGENT,
+};
+use reqwest::{RequestBuilder, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
 use crate::error::{AppError, Result};
 use crate::models::*;
+use super::rate_limit::{mark_rate_limited, rate_limited_until, record_rate_limit, store_rate_limit};
 
-const GITHUB_API_BASE: &str = "https://api.github.com";
+// PRs looked up per GraphQL request, well within GitHub's node limits
+const ENRICH_BATCH_SIZE: usize = 50;
+
+// Viewer plus the brief's three searches in one round-trip. Search nodes spread both summary
+// fragments since results are a union; the is:pr / is:issue qualifiers decide which applies.
+const OVERVIEW_QUERY: &str = r#"
+query($reviewQuery: String!, $authoredQuery: String!, $mentionsQuery: String!, $first: Int!) {
+  viewer { login avatarUrl name }
+  reviewRequested: search(query: $reviewQuery, type: ISSUE, first: $first) { ...SearchResults }
+  authored: search(query: $authoredQuery, type: ISSUE, first: $first) { ...SearchResults }
+  mentioned: search(query: $mentionsQuery, type: ISSUE, first: $first) { ...SearchResults }
+}
+"#;
+
+// Later pages of one of the overview's searches
+const SEARCH_PAGE_QUERY: &str = r#"
+query($query: String!, $first: Int!, $after: String) {
+  search(query: $query, type: ISSUE, first: $first, after: $after) { ...SearchResults }
+}
+"#;
+
+const SEARCH_FRAGMENTS: &str = r#"
+fragment SearchResults on SearchResultItemConnection {
+  issueCount
+  pageInfo { hasNextPage endCursor }
+  nodes { ...PullRequestSummary ...IssueSummary }
+}
+
+fragment PullRequestSummary on PullRequest {
+  fullDatabaseId
+  number
+  title
+  state
+  url
+  createdAt
+  updatedAt
+  author { login avatarUrl }
+  repository { nameWithOwner }
+  labels(first: 20) { nodes { name color } }
+  ...PullRequestDetails
+}
+
+fragment IssueSummary on Issue {
+  fullDatabaseId
+  number
+  title
+  state
+  url
+  body
+  author { login avatarUrl }
+  repository { nameWithOwner }
+  labels(first: 20) { nodes { name color } }
+}
+"#;
+
+// What the search API leaves out of a PR: review state, requested reviewers, CI and mergeability
+const PULL_REQUEST_DETAILS: &str = r#"
+fragment PullRequestDetails on PullRequest {
+  isDraft
+  reviewDecision
+  mergeable
+  latestReviews(first: 20) { nodes { state } }
+  reviewRequests(first: 20) {
+    nodes { requestedReviewer { ... on User { login } ... on Team { slug } } }
+  }
+  commits(last: 1) { nodes { commit { statusCheckRollup { state } } } }
+}
+"#;
+
+// Largest page size the REST API accepts
+const MAX_PER_PAGE: usize = 100;
+
+// Extra attempts after a server error or a short rate-limit wait
+const MAX_RETRIES: u32 = 2;
+
+// Rate-limit waits longer than this are reported rather than slept through
+const MAX_RETRY_WAIT_SECS: i64 = 10;
+
+// GitHub's advice for secondary rate limits that don't say how long to wait
+const SECONDARY_LIMIT_WAIT_SECS: i64 = 60;
+
+// Result of a conditional notifications poll. Both carry the validators to store for the next
+// poll; a 304 keeps the previous Last-Modified but may change the poll interval.
+pub enum NotificationsFetch {
+    Updated(Vec<GitHubNotification>, CacheValidators),
+    NotModified(CacheValidators),
+}
 
 pub struct GitHubService {
     client: reqwest::Client,
     token: String,
+    max_items: usize,
+    endpoints: GitHubEndpoints,
+    account: String, // Tags fetched items and keys rate-limit tracking
 }
 
 impl GitHubService {
@@ -29,12 +126,37 @@ impl GitHubService {
             .default_headers(headers)
             .build()?;
 
-        Ok(Self { client, token })
+        Ok(Self {
+            client,
+            token,
+            max_items: DEFAULT_GITHUB_MAX_ITEMS,
+            endpoints: GitHubEndpoints::default(),
+            account: String::new(),
+        })
+    }
+
+    /// Fetches as `account`, against its server.
+    pub fn with_account(self, account: &GitHubAccount) -> Self {
+        let mut service = self.with_endpoints(account.endpoints.clone());
+        service.account = account.id.clone();
+        service
+    }
+
+    /// Talks to a GitHub Enterprise Server instead of github.com.
+    pub fn with_endpoints(mut self, endpoints: GitHubEndpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    /// Caps how many items each list fetches across pages.
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.max_items = max_items.max(1);
+        self
     }
 
     pub async fn get_current_user(&self) -> Result<GitHubUser> {
-        let url = format!("{}/user", GITHUB_API_BASE);
-        let response = self.client.get(&url).send().await?;
+        let url = format!("{}/user", self.endpoints.api_url);
+        let response = self.send(self.client.get(&url)).await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -53,99 +175,202 @@ impl GitHubService {
         })
     }
 
-    pub async fn get_prs_to_review(&self, username: &str) -> Result<Vec<GitHubPullRequest>> {
-        let query = format!("is:open is:pr review-requested:{} archived:false", username);
-        let url = format!(
-            "{}/search/issues?q={}&sort=updated&order=desc&per_page=20",
-            GITHUB_API_BASE,
-            urlencoding::encode(&query)
-        );
-
-        let response = self.client.get(&url).send().await?;
-
-        if !response.status().is_success() {
-            return Ok(Vec::new());
+    /// Fetches the user, PRs awaiting their review, their open PRs with review state and CI,
+    /// and issues mentioning them. Uses a single GraphQL query when possible and falls back
+    /// to the REST search API (plus enrichment) when GraphQL is unavailable.
+    pub async fn get_overview(&self) -> Result<GitHubOverview> {
+        match self.get_overview_graphql().await {
+            Ok(overview) => Ok(overview),
+            Err(e) => {
+                log::warn!("GitHub GraphQL unavailable, falling back to REST: {}", e);
+                self.get_overview_rest().await
+            }
         }
+    }
 
-        let search_result: GitHubApiSearchResult<GitHubApiIssue> = response.json().await?;
+    async fn get_overview_graphql(&self) -> Result<GitHubOverview> {
+        let review_query = "is:open is:pr review-requested:@me archived:false sort:updated-desc";
+        let authored_query = "is:open is:pr author:@me archived:false sort:updated-desc";
+        let mentions_query = "is:open is:issue mentions:@me archived:false sort:updated-desc";
 
-        let prs: Vec<GitHubPullRequest> = search_result
-            .items
-            .into_iter()
-            .filter(|item| item.pull_request.is_some())
-            .map(|item| self.issue_to_pr(item))
-            .collect();
+        let data: GitHubGraphQlOverview = self
+            .graphql(
+                &format!("{}{}{}", OVERVIEW_QUERY, SEARCH_FRAGMENTS, PULL_REQUEST_DETAILS),
+                json!({
+                    "reviewQuery": review_query,
+                    "authoredQuery": authored_query,
+                    "mentionsQuery": mentions_query,
+                    "first": self.per_page(),
+                }),
+            )
+            .await?;
 
-        Ok(prs)
+        let (prs_to_review, my_open_prs, mentioned_issues) = tokio::try_join!(
+            self.collect_search(review_query, data.review_requested),
+            self.collect_search(authored_query, data.authored),
+            self.collect_search(mentions_query, data.mentioned)
+        )?;
+
+        Ok(GitHubOverview {
+            user: GitHubUser {
+                login: data.viewer.login,
+                avatar_url: data.viewer.avatar_url,
+                name: data.viewer.name,
+            },
+            prs_to_review: map_search(prs_to_review, |node| graphql_pr_to_pr(&self.account, node)),
+            my_open_prs: map_search(my_open_prs, |node| graphql_pr_to_pr(&self.account, node)),
+            mentioned_issues: map_search(mentioned_issues, |node| graphql_issue_to_issue(&self.account, node)),
+        })
     }
 
-    pub async fn get_my_open_prs(&self, username: &str) -> Result<Vec<GitHubPullRequest>> {
-        let query = format!("is:open is:pr author:{} archived:false", username);
-        let url = format!(
-            "{}/search/issues?q={}&sort=updated&order=desc&per_page=20",
-            GITHUB_API_BASE,
-            urlencoding::encode(&query)
-        );
+    async fn get_overview_rest(&self) -> Result<GitHubOverview> {
+        let user = self.get_current_user().await?;
 
-        let response = self.client.get(&url).send().await?;
+        let (mut prs_to_review, mut my_open_prs, mentioned_issues) = tokio::try_join!(
+            self.get_prs_to_review(&user.login),
+            self.get_my_open_prs(&user.login),
+            self.get_mentioned_issues(&user.login)
+        )?;
 
-        if !response.status().is_success() {
-            return Ok(Vec::new());
+        // Review state and CI come from GraphQL; the lists are still useful without them
+        let (review_details, mine_details) = tokio::join!(
+            self.enrich_pull_requests(&mut prs_to_review.items),
+            self.enrich_pull_requests(&mut my_open_prs.items)
+        );
+        if let Err(e) = review_details.and(mine_details) {
+            log::warn!("Failed to fetch pull request details: {}", e);
         }
 
-        let search_result: GitHubApiSearchResult<GitHubApiIssue> = response.json().await?;
+        Ok(GitHubOverview {
+            user,
+            prs_to_review,
+            my_open_prs,
+            mentioned_issues,
+        })
+    }
+
+    // Follows a GraphQL search's cursor from its first page until `max_items` are collected
+    async fn collect_search<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        first_page: GitHubGraphQlSearch<T>,
+    ) -> Result<GitHubSearchResults<T>> {
+        let total_count = first_page.issue_count;
+        let mut items: Vec<T> = first_page.nodes.into_iter().flatten().collect();
+        let mut page_info = first_page.page_info;
 
-        let prs: Vec<GitHubPullRequest> = search_result
-            .items
-            .into_iter()
-            .filter(|item| item.pull_request.is_some())
-            .map(|item| self.issue_to_pr(item))
-            .collect();
+        while page_info.has_next_page && items.len() < self.max_items {
+            let page: GitHubGraphQlSearchPage<T> = match self
+                .graphql(
+                    &format!("{}{}{}", SEARCH_PAGE_QUERY, SEARCH_FRAGMENTS, PULL_REQUEST_DETAILS),
+                    json!({ "query": query, "first": self.per_page(), "after": page_info.end_cursor }),
+                )
+                .await
+            {
+                Ok(page) => page,
+                Err(e) => {
+                    log::warn!("Stopped paging GitHub search early: {}", e);
+                    break;
+                }
+            };
+            items.extend(page.search.nodes.into_iter().flatten());
+            page_info = page.search.page_info;
+        }
 
-        Ok(prs)
+        items.truncate(self.max_items);
+        Ok(GitHubSearchResults { items, total_count })
     }
 
-    pub async fn get_mentioned_issues(&self, username: &str) -> Result<Vec<GitHubIssue>> {
-        let query = format!("is:open mentions:{} archived:false", username);
-        let url = format!(
-            "{}/search/issues?q={}&sort=updated&order=desc&per_page=20",
-            GITHUB_API_BASE,
-            urlencoding::encode(&query)
-        );
+    pub async fn get_prs_to_review(&self, username: &str) -> Result<GitHubSearchResults<GitHubPullRequest>> {
+        let query = format!("is:open is:pr review-requested:{} archived:false", username);
+        let results = self.search_issues(&query).await?;
 
-        let response = self.client.get(&url).send().await?;
+        Ok(GitHubSearchResults {
+            items: results
+                .items
+                .into_iter()
+                .filter(|item| item.pull_request.is_some())
+                .map(|item| self.issue_to_pr(item))
+                .collect(),
+            total_count: results.total_count,
+        })
+    }
 
-        if !response.status().is_success() {
-            return Ok(Vec::new());
-        }
+    pub async fn get_my_open_prs(&self, username: &str) -> Result<GitHubSearchResults<GitHubPullRequest>> {
+        let query = format!("is:open is:pr author:{} archived:false", username);
+        let results = self.search_issues(&query).await?;
 
-        let search_result: GitHubApiSearchResult<GitHubApiIssue> = response.json().await?;
+        Ok(GitHubSearchResults {
+            items: results
+                .items
+                .into_iter()
+                .filter(|item| item.pull_request.is_some())
+                .map(|item| self.issue_to_pr(item))
+                .collect(),
+            total_count: results.total_count,
+        })
+    }
 
-        let issues: Vec<GitHubIssue> = search_result
-            .items
-            .into_iter()
-            .filter(|item| item.pull_request.is_none()) // Exclude PRs
-            .map(|item| self.api_issue_to_issue(item))
-            .collect();
+    pub async fn get_mentioned_issues(&self, username: &str) -> Result<GitHubSearchResults<GitHubIssue>> {
+        // is:issue keeps PRs out of the total as well as the list
+        let query = format!("is:open is:issue mentions:{} archived:false", username);
+        let results = self.search_issues(&query).await?;
 
-        Ok(issues)
+        Ok(GitHubSearchResults {
+            items: results
+                .items
+                .into_iter()
+                .filter(|item| item.pull_request.is_none()) // Exclude PRs
+                .map(|item| self.api_issue_to_issue(item))
+                .collect(),
+            total_count: results.total_count,
+        })
     }
 
-    pub async fn get_notifications(&self) -> Result<Vec<GitHubNotification>> {
-        let url = format!("{}/notifications?per_page=30", GITHUB_API_BASE);
+    /// Polls notifications with If-Modified-Since from `validators`. GitHub answers 304 when
+    /// nothing changed, which doesn't count against the rate limit.
+    pub async fn get_notifications(&self, validators: &CacheValidators) -> Result<NotificationsFetch> {
+        let url = format!("{}/notifications?per_page={}", self.endpoints.api_url, self.per_page());
+        let mut request = self.client.get(&url);
+        if let Some(ref last_modified) = validators.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(request).await?;
+        let poll_interval = response
+            .headers()
+            .get("x-poll-interval")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok());
 
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(NotificationsFetch::NotModified(CacheValidators {
+                poll_interval: poll_interval.or(validators.poll_interval),
+                ..validators.clone()
+            }));
+        }
         if !response.status().is_success() {
-            return Ok(Vec::new());
+            return Err(api_error(response).await);
         }
 
-        let api_notifications: Vec<GitHubApiNotification> = response.json().await?;
+        let validators = CacheValidators {
+            etag: None,
+            last_modified: response
+                .headers()
+                .get(LAST_MODIFIED)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from),
+            poll_interval,
+        };
+        let next = next_page_url(response.headers());
+        let first_page: Vec<GitHubApiNotification> = response.json().await?;
+        let api_notifications = self.follow_pages(first_page, next, |page| page).await;
 
         let notifications: Vec<GitHubNotification> = api_notifications
             .into_iter()
             .map(|n| GitHubNotification {
                 id: n.id,
+                account: self.account.clone(),
                 repo_full_name: n.repository.full_name,
                 subject_title: n.subject.title,
                 subject_type: n.subject.subject_type,
@@ -156,7 +381,273 @@ impl GitHubService {
             })
             .collect();
 
-        Ok(notifications)
+        Ok(NotificationsFetch::Updated(notifications, validators))
+    }
+
+    /// Marks a notification thread as read.
+    pub async fn mark_thread_read(&self, thread_id: &str) -> Result<()> {
+        let url = format!("{}/notifications/threads/{}", self.endpoints.api_url, thread_id);
+        self.send_checked(self.client.patch(&url)).await
+    }
+
+    /// Marks a notification thread as done, removing it from the inbox.
+    pub async fn mark_thread_done(&self, thread_id: &str) -> Result<()> {
+        let url = format!("{}/notifications/threads/{}", self.endpoints.api_url, thread_id);
+        self.send_checked(self.client.delete(&url)).await
+    }
+
+    /// Marks every notification in `owner/repo` as read.
+    pub async fn mark_repo_notifications_read(&self, repo_full_name: &str) -> Result<()> {
+        let url = format!("{}/repos/{}/notifications", self.endpoints.api_url, repo_full_name);
+        self.send_checked(self.client.put(&url).json(&json!({}))).await
+    }
+
+    /// Stops notifications for a thread until the user is mentioned or participates again.
+    pub async fn unsubscribe_thread(&self, thread_id: &str) -> Result<()> {
+        let url = format!("{}/notifications/threads/{}/subscription", self.endpoints.api_url, thread_id);
+        self.send_checked(self.client.delete(&url)).await
+    }
+
+    /// Asks GitHub for the remaining budget of every resource and remembers it. Checking
+    /// doesn't count against the limit, so this works even when the budget is used up.
+    pub async fn refresh_rate_limits(&self) -> Result<()> {
+        let url = format!("{}/rate_limit", self.endpoints.api_url);
+        let response = self.client.get(&url).send().await?;
+        if !response.status().is_success() {
+            return Err(api_error(response).await);
+        }
+
+        let limits: GitHubApiRateLimits = response.json().await?;
+        for (resource, limit) in limits.resources {
+            store_rate_limit(GitHubRateLimit {
+                account: self.account.clone(),
+                resource,
+                limit: limit.limit,
+                remaining: limit.remaining,
+                used: limit.used,
+                reset: limit.reset,
+            });
+        }
+        Ok(())
+    }
+
+    /// Fills in what the search API doesn't return (review state, requested reviewers, CI
+    /// state and mergeability) with one GraphQL request per batch of PRs. PRs GitHub can't
+    /// resolve are left as they were.
+    pub async fn enrich_pull_requests(&self, prs: &mut [GitHubPullRequest]) -> Result<()> {
+        for batch in prs.chunks_mut(ENRICH_BATCH_SIZE) {
+            let mut declarations = Vec::new();
+            let mut fields = Vec::new();
+            let mut variables = serde_json::Map::new();
+
+            for (i, pr) in batch.iter().enumerate() {
+                let Some((owner, name)) = pr.repo_full_name.split_once('/') else {
+                    continue;
+                };
+                declarations.push(format!("$owner{i}: String!, $name{i}: String!, $number{i}: Int!"));
+                fields.push(format!(
+                    "pr{i}: repository(owner: $owner{i}, name: $name{i}) {{ pullRequest(number: $number{i}) {{ ...PullRequestDetails }} }}"
+                ));
+                variables.insert(format!("owner{i}"), json!(owner));
+                variables.insert(format!("name{i}"), json!(name));
+                variables.insert(format!("number{i}"), json!(pr.number));
+            }
+
+            if fields.is_empty() {
+                continue;
+            }
+
+            let query = format!(
+                "query({}) {{\n{}\n}}\n{}",
+                declarations.join(", "),
+                fields.join("\n"),
+                PULL_REQUEST_DETAILS
+            );
+            let mut data: HashMap<String, Option<GitHubGraphQlRepository>> =
+                self.graphql(&query, Value::Object(variables)).await?;
+
+            for (i, pr) in batch.iter_mut().enumerate() {
+                if let Some(details) = data.remove(&format!("pr{i}")).flatten().and_then(|r| r.pull_request) {
+                    apply_pull_request_details(pr, details);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Returns `data` even when some fields failed (e.g. a repo the token can't see); those
+    // come back as null and their errors are only logged
+    async fn graphql<T: DeserializeOwned>(&self, query: &str, variables: Value) -> Result<T> {
+        let response = self
+            .send(
+                self.client
+                    .post(&self.endpoints.graphql_url)
+                    .json(&json!({ "query": query, "variables": variables })),
+            )
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::Other(format!("GitHub GraphQL error ({}): {}", status, body)));
+        }
+
+        let body: GitHubGraphQlResponse<T> = response.json().await?;
+        for error in &body.errors {
+            log::warn!("GitHub GraphQL: {}", error.message);
+        }
+
+        // GraphQL reports an exhausted budget as a 200 with a RATE_LIMITED error
+        if body.data.is_none() && body.errors.iter().any(|e| e.error_type.as_deref() == Some("RATE_LIMITED")) {
+            let now = chrono::Utc::now().timestamp();
+            let until = rate_limited_until(&self.account, "graphql", now).unwrap_or(now + SECONDARY_LIMIT_WAIT_SECS);
+            mark_rate_limited(&self.account, "graphql", until);
+            return Err(AppError::RateLimited(until));
+        }
+
+        body.data.ok_or_else(|| {
+            let messages: Vec<&str> = body.errors.iter().map(|e| e.message.as_str()).collect();
+            AppError::Other(format!("GitHub GraphQL error: {}", messages.join("; ")))
+        })
+    }
+
+    async fn search_issues(&self, query: &str) -> Result<GitHubSearchResults<GitHubApiIssue>> {
+        let url = format!(
+            "{}/search/issues?q={}&sort=updated&order=desc&per_page={}",
+            self.endpoints.api_url,
+            urlencoding::encode(query),
+            self.per_page()
+        );
+        let mut total_count = 0;
+        let items = self
+            .get_pages(url, |page: GitHubApiSearchResult<GitHubApiIssue>| {
+                total_count = page.total_count;
+                page.items
+            })
+            .await?;
+
+        Ok(GitHubSearchResults { items, total_count })
+    }
+
+    // Fetches `url` and follows `Link: rel="next"` from it; a failed first page is an error
+    async fn get_pages<P: DeserializeOwned, T>(&self, url: String, mut items_of: impl FnMut(P) -> Vec<T>) -> Result<Vec<T>> {
+        let (page, next) = self.get_page(&url).await?;
+        let first_page = items_of(page);
+        Ok(self.follow_pages(first_page, next, items_of).await)
+    }
+
+    // Adds pages from `url` onwards until `max_items` are collected. A failed page ends the
+    // list with what was fetched so far.
+    async fn follow_pages<P: DeserializeOwned, T>(
+        &self,
+        mut items: Vec<T>,
+        mut url: Option<String>,
+        mut items_of: impl FnMut(P) -> Vec<T>,
+    ) -> Vec<T> {
+        while items.len() < self.max_items {
+            let Some(page_url) = url.take() else {
+                break;
+            };
+
+            let page = match self.get_page(&page_url).await {
+                Ok((page, next)) => {
+                    url = next;
+                    items_of(page)
+                }
+                Err(e) => {
+                    log::warn!("Stopped paging GitHub results early: {}", e);
+                    break;
+                }
+            };
+
+            if page.is_empty() {
+                break;
+            }
+            items.extend(page);
+        }
+
+        items.truncate(self.max_items);
+        items
+    }
+
+    // For requests whose response body isn't needed
+    async fn send_checked(&self, request: RequestBuilder) -> Result<()> {
+        let response = self.send(request).await?;
+        if !response.status().is_success() {
+            return Err(api_error(response).await);
+        }
+        Ok(())
+    }
+
+    async fn get_page<P: DeserializeOwned>(&self, url: &str) -> Result<(P, Option<String>)> {
+        let response = self.send(self.client.get(url)).await?;
+        if !response.status().is_success() {
+            return Err(api_error(response).await);
+        }
+
+        let next = next_page_url(response.headers());
+        Ok((response.json().await?, next))
+    }
+
+    // Every API request goes through here. Refuses to spend a budget that's known to be used
+    // up, records the budget GitHub reports, and retries with jittered backoff on server
+    // errors and on rate limits that ask for only a short wait.
+    async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        let request = request.build()?;
+        let resource = rate_limit_resource(request.url());
+        let mut attempt = 0;
+
+        loop {
+            let now = chrono::Utc::now().timestamp();
+            if let Some(until) = rate_limited_until(&self.account, resource, now) {
+                return Err(AppError::RateLimited(until));
+            }
+
+            let retry = request
+                .try_clone()
+                .ok_or_else(|| AppError::Other("GitHub request can't be retried".to_string()))?;
+            let response = self.client.execute(retry).await?;
+            record_rate_limit(&self.account, response.headers());
+
+            let status = response.status();
+            let limited_until = match response_limit(status, response.headers(), now) {
+                ResponseLimit::None => None,
+                ResponseLimit::Until(until) => Some(until),
+                ResponseLimit::InBody => {
+                    // Secondary limits only say so in the body; anything else is a
+                    // permissions problem
+                    let body = response.text().await.unwrap_or_default();
+                    let Some(until) = secondary_limit_until(&body, now) else {
+                        return Err(AppError::Other(format!("GitHub API error ({}): {}", status, body)));
+                    };
+                    mark_rate_limited(&self.account, resource, until);
+                    return Err(AppError::RateLimited(until));
+                }
+            };
+
+            if let Some(until) = limited_until {
+                if let Some(wait) = rate_limit_retry_wait(attempt, until, now) {
+                    attempt += 1;
+                    tokio::time::sleep(wait + jitter()).await;
+                    continue;
+                }
+                mark_rate_limited(&self.account, resource, until);
+                return Err(AppError::RateLimited(until));
+            }
+
+            if let Some(wait) = server_error_retry_wait(status, attempt) {
+                attempt += 1;
+                tokio::time::sleep(wait + jitter()).await;
+                continue;
+            }
+
+            return Ok(response);
+        }
+    }
+
+    fn per_page(&self) -> usize {
+        self.max_items.min(MAX_PER_PAGE)
     }
 
     fn issue_to_pr(&self, item: GitHubApiIssue) -> GitHubPullRequest {
@@ -164,37 +655,35 @@ impl GitHubService {
 
         GitHubPullRequest {
             id: item.id,
+            account: self.account.clone(),
             repo_full_name,
             number: item.number,
             title: item.title,
             state: item.state,
-            draft: false, // Draft status not available from search API
+            draft: item.draft.unwrap_or(false),
             user_login: item.user.login,
             user_avatar_url: Some(item.user.avatar_url),
             html_url: item.html_url,
-            created_at: String::new(),
-            updated_at: String::new(),
-            requested_reviewers: Vec::new(),
+            created_at: item.created_at,
+            updated_at: item.updated_at,
+            requested_reviewers: Vec::new(), // Filled in by enrich_pull_requests
             labels: item.labels.into_iter().map(|l| GitHubLabel {
                 name: l.name,
                 color: l.color,
             }).collect(),
             review_status: None,
+            ci_status: None,
+            mergeable: None,
         }
     }
 
     fn api_issue_to_issue(&self, item: GitHubApiIssue) -> GitHubIssue {
         let repo_full_name = self.extract_repo_from_url(&item.html_url);
-        let body_preview = item.body.map(|b| {
-            if b.len() > 200 {
-                format!("{}...", &b[..197])
-            } else {
-                b
-            }
-        });
+        let body_preview = item.body.map(body_preview);
 
         GitHubIssue {
             id: item.id,
+            account: self.account.clone(),
             repo_full_name,
             number: item.number,
             title: item.title,
@@ -211,19 +700,414 @@ impl GitHubService {
 
     fn extract_repo_from_url(&self, url: &str) -> String {
         // Extract owner/repo from URLs like https://github.com/owner/repo/...
-        let parts: Vec<&str> = url.split('/').collect();
-        if parts.len() >= 5 {
-            format!("{}/{}", parts[3], parts[4])
-        } else {
-            String::new()
+        let web_url = self.endpoints.web_url();
+        let path = url
+            .strip_prefix(web_url.as_str())
+            .map(|rest| rest.trim_start_matches('/'))
+            .unwrap_or_else(|| url.splitn(4, '/').nth(3).unwrap_or_default());
+        let mut parts = path.split('/');
+        match (parts.next(), parts.next()) {
+            (Some(owner), Some(repo)) if !owner.is_empty() && !repo.is_empty() => format!("{}/{}", owner, repo),
+            _ => String::new(),
         }
     }
 
     fn convert_api_url_to_html(&self, api_url: &str) -> String {
         // Convert https://api.github.com/repos/owner/repo/issues/123
-        // to https://github.com/owner/repo/issues/123
-        api_url
-            .replace("api.github.com/repos", "github.com")
-            .replace("/pulls/", "/pull/")
+        // to https://github.com/owner/repo/issues/123 (likewise for GHES's /api/v3)
+        let repos_prefix = format!("{}/repos/", self.endpoints.api_url);
+        match api_url.strip_prefix(repos_prefix.as_str()) {
+            Some(path) => format!("{}/{}", self.endpoints.web_url(), path.replace("/pulls/", "/pull/")),
+            None => api_url.to_string(),
+        }
+    }
+}
+
+async fn api_error(response: Response) -> AppError {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    AppError::Other(format!("GitHub API error ({}): {}", status, body))
+}
+
+// Which of GitHub's separately metered budgets a request draws from
+fn rate_limit_resource(url: &Url) -> &'static str {
+    let path = url.path();
+    if path.ends_with("/graphql") {
+        "graphql"
+    } else if path.contains("/search/") {
+        "search"
+    } else {
+        "core"
+    }
+}
+
+// When a rate-limited response says the budget comes back: Retry-After for secondary limits,
+// X-RateLimit-Reset once the primary budget is spent
+fn rate_limit_reset(headers: &HeaderMap, now: i64) -> Option<i64> {
+    let number = |name: &str| headers.get(name)?.to_str().ok()?.parse::<i64>().ok();
+
+    if let Some(secs) = number(RETRY_AFTER.as_str()) {
+        return Some(now + secs);
+    }
+    if number("x-ratelimit-remaining") == Some(0) {
+        return number("x-ratelimit-reset");
+    }
+    None
+}
+
+// How a response bears on the rate limit
+#[derive(Debug, PartialEq)]
+enum ResponseLimit {
+    None,
+    Until(i64),
+    InBody, // A 403 without budget headers: only the body tells a secondary limit from a denial
+}
+
+fn response_limit(status: StatusCode, headers: &HeaderMap, now: i64) -> ResponseLimit {
+    match (status, rate_limit_reset(headers, now)) {
+        (StatusCode::TOO_MANY_REQUESTS, until) => ResponseLimit::Until(until.unwrap_or(now + SECONDARY_LIMIT_WAIT_SECS)),
+        (StatusCode::FORBIDDEN, Some(until)) => ResponseLimit::Until(until),
+        (StatusCode::FORBIDDEN, None) => ResponseLimit::InBody,
+        _ => ResponseLimit::None,
+    }
+}
+
+fn secondary_limit_until(body: &str, now: i64) -> Option<i64> {
+    body.to_ascii_lowercase()
+        .contains("rate limit")
+        .then_some(now + SECONDARY_LIMIT_WAIT_SECS)
+}
+
+// Waits out a rate limit in place only when it lifts within a few seconds; longer ones are
+// left to the next refresh
+fn rate_limit_retry_wait(attempt: u32, until: i64, now: i64) -> Option<Duration> {
+    (attempt < MAX_RETRIES && until - now <= MAX_RETRY_WAIT_SECS)
+        .then(|| Duration::from_secs((until - now).max(1) as u64))
+}
+
+fn server_error_retry_wait(status: StatusCode, attempt: u32) -> Option<Duration> {
+    (status.is_server_error() && attempt < MAX_RETRIES).then(|| Duration::from_secs(1 << (attempt + 1)))
+}
+
+// Up to a second, so concurrent retries don't hit GitHub in lockstep
+fn jitter() -> Duration {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+    Duration::from_millis(u64::from(nanos % 1000))
+}
+
+// The `rel="next"` target of a Link header: `<https://...&page=2>; rel="next", <...>; rel="last"`
+fn next_page_url(headers: &HeaderMap) -> Option<String> {
+    let link = headers.get(LINK)?.to_str().ok()?;
+    // Targets sit in angle brackets and may contain commas, so split entries on those
+    link.split('<').skip(1).find_map(|entry| {
+        let (target, params) = entry.split_once('>')?;
+        let is_next = params
+            .split(';')
+            .filter_map(|param| param.split_once('='))
+            .filter(|(name, _)| name.trim().eq_ignore_ascii_case("rel"))
+            .any(|(_, value)| {
+                let value = value.trim().trim_end_matches(',').trim().trim_matches('"');
+                value.split_whitespace().any(|rel| rel.eq_ignore_ascii_case("next"))
+            });
+        is_next.then(|| target.trim().to_string())
+    })
+}
+
+fn apply_pull_request_details(pr: &mut GitHubPullRequest, details: GitHubGraphQlPullRequest) {
+    let latest_reviews: Vec<String> = details.latest_reviews.nodes.into_iter().flatten().map(|r| r.state).collect();
+
+    // reviewDecision is only set when the branch requires reviews; otherwise go by the
+    // latest review of each reviewer
+    let review_status = match details.review_decision.as_deref() {
+        Some("APPROVED") => "approved",
+        Some("CHANGES_REQUESTED") => "changes_requested",
+        Some(_) => "pending",
+        None if latest_reviews.iter().any(|s| s == "CHANGES_REQUESTED") => "changes_requested",
+        None if latest_reviews.iter().any(|s| s == "APPROVED") => "approved",
+        None => "pending",
+    };
+
+    let ci_status = details
+        .commits
+        .nodes
+        .into_iter()
+        .flatten()
+        .next()
+        .and_then(|node| node.commit.status_check_rollup)
+        .and_then(|rollup| match rollup.state.as_str() {
+            "SUCCESS" => Some("success"),
+            "FAILURE" | "ERROR" => Some("failure"),
+            "PENDING" | "EXPECTED" => Some("pending"),
+            _ => None,
+        });
+
+    pr.draft = details.is_draft;
+    pr.review_status = Some(review_status.to_string());
+    pr.ci_status = ci_status.map(String::from);
+    pr.mergeable = Some(details.mergeable.to_ascii_lowercase());
+    pr.requested_reviewers = details
+        .review_requests
+        .nodes
+        .into_iter()
+        .flatten()
+        .filter_map(|request| request.requested_reviewer)
+        .filter_map(|reviewer| reviewer.login.or(reviewer.slug))
+        .collect();
+}
+
+fn map_search<T, U>(results: GitHubSearchResults<T>, convert: impl Fn(T) -> U) -> GitHubSearchResults<U> {
+    GitHubSearchResults {
+        items: results.items.into_iter().map(convert).collect(),
+        total_count: results.total_count,
+    }
+}
+
+fn graphql_pr_to_pr(account: &str, node: GitHubGraphQlPullRequestSummary) -> GitHubPullRequest {
+    let author = node.author;
+    let mut pr = GitHubPullRequest {
+        id: node.full_database_id.and_then(|id| id.parse().ok()).unwrap_or_default(),
+        account: account.to_string(),
+        repo_full_name: node.repository.name_with_owner,
+        number: node.number,
+        title: node.title,
+        state: node.state.to_ascii_lowercase(),
+        draft: false,
+        // Deleted accounts come back as a null author
+        user_login: author.as_ref().map(|a| a.login.clone()).unwrap_or_else(|| "ghost".to_string()),
+        user_avatar_url: author.map(|a| a.avatar_url),
+        html_url: node.url,
+        created_at: node.created_at,
+        updated_at: node.updated_at,
+        requested_reviewers: Vec::new(),
+        labels: graphql_labels(node.labels),
+        review_status: None,
+        ci_status: None,
+        mergeable: None,
+    };
+    apply_pull_request_details(&mut pr, node.details);
+    pr
+}
+
+fn graphql_issue_to_issue(account: &str, node: GitHubGraphQlIssue) -> GitHubIssue {
+    GitHubIssue {
+        id: node.full_database_id.and_then(|id| id.parse().ok()).unwrap_or_default(),
+        account: account.to_string(),
+        repo_full_name: node.repository.name_with_owner,
+        number: node.number,
+        title: node.title,
+        state: node.state.to_ascii_lowercase(),
+        user_login: node.author.map(|a| a.login).unwrap_or_else(|| "ghost".to_string()),
+        html_url: node.url,
+        body_preview: node.body.map(body_preview),
+        labels: graphql_labels(node.labels),
+    }
+}
+
+fn graphql_labels(labels: GitHubGraphQlNodes<GitHubGraphQlLabel>) -> Vec<GitHubLabel> {
+    labels
+        .nodes
+        .into_iter()
+        .flatten()
+        .map(|l| GitHubLabel {
+            name: l.name,
+            color: l.color,
+        })
+        .collect()
+}
+
+// First 200 characters of an issue body, cut on a character boundary
+fn body_preview(body: String) -> String {
+    if body.chars().count() > 200 {
+        format!("{}...", body.chars().take(197).collect::<String>())
+    } else {
+        body
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    fn link(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(LINK, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    // Serves `pages` pages of three numbers at /items?page=N, each linking to the next,
+    // and counts the requests
+    async fn paged_server(pages: usize) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let (count, server) = (requests.clone(), base.clone());
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                count.fetch_add(1, Ordering::SeqCst);
+                let server = server.clone();
+                tokio::spawn(async move {
+                    let mut stream = BufReader::new(stream);
+                    let mut line = String::new();
+                    stream.read_line(&mut line).await.unwrap();
+                    let page: usize = line
+                        .split_whitespace()
+                        .nth(1)
+                        .and_then(|path| path.rsplit_once('='))
+                        .and_then(|(_, page)| page.parse().ok())
+                        .unwrap_or(1);
+                    loop {
+                        line.clear();
+                        if stream.read_line(&mut line).await.unwrap() == 0 || line.trim_end().is_empty() {
+                            break;
+                        }
+                    }
+
+                    let body = serde_json::to_string(&((page - 1) * 3 + 1..=page * 3).collect::<Vec<_>>()).unwrap();
+                    let next = if page < pages {
+                        format!("Link: <{}/items?page={}>; rel=\"next\", <{}/items?page={}>; rel=\"last\"\r\n", server, page + 1, server, pages)
+                    } else {
+                        String::new()
+                    };
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        next,
+                        body.len(),
+                        body
+                    );
+                    stream.get_mut().write_all(response.as_bytes()).await.unwrap();
+                });
+            }
+        });
+
+        (base, requests)
+    }
+
+    #[test]
+    fn finds_next_among_other_rels() {
+        let headers = link(
+            r#"<https://api.github.com/search/issues?q=a&page=1>; rel="prev", <https://api.github.com/search/issues?q=a&page=3>; rel="next", <https://api.github.com/search/issues?q=a&page=5>; rel="last", <https://api.github.com/search/issues?q=a&page=1>; rel="first""#,
+        );
+        assert_eq!(
+            next_page_url(&headers).as_deref(),
+            Some("https://api.github.com/search/issues?q=a&page=3")
+        );
+    }
+
+    #[test]
+    fn reads_next_from_a_combined_rel() {
+        let headers = link(r#"<https://api.github.com/notifications?page=2>; rel="next last""#);
+        assert_eq!(next_page_url(&headers).as_deref(), Some("https://api.github.com/notifications?page=2"));
+    }
+
+    #[test]
+    fn keeps_commas_inside_link_targets() {
+        let headers = link(r#"<https://api.github.com/search/issues?q=label:a,b&page=2>; rel="next""#);
+        assert_eq!(
+            next_page_url(&headers).as_deref(),
+            Some("https://api.github.com/search/issues?q=label:a,b&page=2")
+        );
+    }
+
+    #[test]
+    fn has_no_next_page_on_the_last_page() {
+        let headers = link(
+            r#"<https://api.github.com/notifications?page=1>; rel="first", <https://api.github.com/notifications?page=4>; rel="prev""#,
+        );
+        assert_eq!(next_page_url(&headers), None);
+        assert_eq!(next_page_url(&HeaderMap::new()), None);
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    const NOW: i64 = 1_770_000_000;
+
+    #[test]
+    fn waits_for_retry_after_on_429() {
+        let retry_after = headers(&[("retry-after", "7")]);
+        assert_eq!(response_limit(StatusCode::TOO_MANY_REQUESTS, &retry_after, NOW), ResponseLimit::Until(NOW + 7));
+        assert_eq!(
+            response_limit(StatusCode::TOO_MANY_REQUESTS, &HeaderMap::new(), NOW),
+            ResponseLimit::Until(NOW + SECONDARY_LIMIT_WAIT_SECS)
+        );
+    }
+
+    #[test]
+    fn waits_for_the_reset_when_the_primary_budget_is_spent() {
+        let spent = headers(&[("x-ratelimit-remaining", "0"), ("x-ratelimit-reset", "1770003600")]);
+        assert_eq!(response_limit(StatusCode::FORBIDDEN, &spent, NOW), ResponseLimit::Until(1_770_003_600));
+
+        // Budget left: a 403 is either a secondary limit or a denial
+        let left = headers(&[("x-ratelimit-remaining", "12"), ("x-ratelimit-reset", "1770003600")]);
+        assert_eq!(response_limit(StatusCode::FORBIDDEN, &left, NOW), ResponseLimit::InBody);
+        assert_eq!(response_limit(StatusCode::OK, &spent, NOW), ResponseLimit::None);
+    }
+
+    #[test]
+    fn tells_secondary_limits_from_denials_by_the_body() {
+        let secondary = r#"{"message":"You have exceeded a secondary rate limit. Please wait a few minutes before you try again."}"#;
+        assert_eq!(secondary_limit_until(secondary, NOW), Some(NOW + SECONDARY_LIMIT_WAIT_SECS));
+        assert_eq!(secondary_limit_until(r#"{"message":"Resource not accessible by integration"}"#, NOW), None);
+    }
+
+    #[test]
+    fn retries_only_short_rate_limits() {
+        assert_eq!(rate_limit_retry_wait(0, NOW + 3, NOW), Some(Duration::from_secs(3)));
+        assert_eq!(rate_limit_retry_wait(0, NOW + MAX_RETRY_WAIT_SECS, NOW), Some(Duration::from_secs(10)));
+        assert_eq!(rate_limit_retry_wait(0, NOW + MAX_RETRY_WAIT_SECS + 1, NOW), None);
+        // A reset already past still waits a moment
+        assert_eq!(rate_limit_retry_wait(1, NOW - 5, NOW), Some(Duration::from_secs(1)));
+        assert_eq!(rate_limit_retry_wait(MAX_RETRIES, NOW + 3, NOW), None);
+    }
+
+    #[test]
+    fn backs_off_on_server_errors_up_to_max_retries() {
+        assert_eq!(server_error_retry_wait(StatusCode::BAD_GATEWAY, 0), Some(Duration::from_secs(2)));
+        assert_eq!(server_error_retry_wait(StatusCode::BAD_GATEWAY, 1), Some(Duration::from_secs(4)));
+        assert_eq!(server_error_retry_wait(StatusCode::BAD_GATEWAY, MAX_RETRIES), None);
+        assert_eq!(server_error_retry_wait(StatusCode::NOT_FOUND, 0), None);
+    }
+
+    #[tokio::test]
+    async fn follows_next_links_to_the_last_page() {
+        let (base, requests) = paged_server(3).await;
+        let service = GitHubService::new("token".to_string()).unwrap();
+
+        let items = service
+            .follow_pages(vec![1, 2, 3], Some(format!("{}/items?page=2", base)), |page: Vec<usize>| page)
+            .await;
+
+        assert_eq!(items, (1..=9).collect::<Vec<_>>());
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn stops_paging_at_max_items() {
+        let (base, requests) = paged_server(3).await;
+        let service = GitHubService::new("token".to_string()).unwrap().with_max_items(5);
+
+        let items = service
+            .follow_pages(vec![1, 2, 3], Some(format!("{}/items?page=2", base)), |page: Vec<usize>| page)
+            .await;
+
+        assert_eq!(items, vec![1, 2, 3, 4, 5]);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // A first page that already fills the cap isn't followed at all
+        let items = service
+            .follow_pages((1..=6).collect(), Some(format!("{}/items?page=3", base)), |page: Vec<usize>| page)
+            .await;
+        assert_eq!(items.len(), 5);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
     }
 }
@@ -1,7 +1,18 @@
 mod github_service;
+mod github_oauth;
 mod calendar_service;
 mod credentials;
+mod caldav;
+mod conference;
+mod recurrence;
+mod rate_limit;
+mod schedule;
+mod timezone;
 
 pub use github_service::*;
+pub use github_oauth::*;
 pub use calendar_service::*;
 pub use credentials::*;
+pub use timezone::*;
+pub use schedule::*;
+pub use rate_limit::*;
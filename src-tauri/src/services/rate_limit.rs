@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use reqwest::header::HeaderMap;
+use crate::models::GitHubRateLimit;
+
+// Last budget GitHub reported per account and resource ("core", "search", "graphql"), kept for
+// the whole process so each refresh starts from what the previous one saw
+static RATE_LIMITS: LazyLock<Mutex<HashMap<(String, String), GitHubRateLimit>>> = LazyLock::new(Default::default);
+
+pub(crate) fn record_rate_limit(account: &str, headers: &HeaderMap) {
+    if let Some(rate_limit) = rate_limit_from_headers(account, headers) {
+        store_rate_limit(rate_limit);
+    }
+}
+
+// The budget in a response's X-RateLimit-* headers, if it reports one
+fn rate_limit_from_headers(account: &str, headers: &HeaderMap) -> Option<GitHubRateLimit> {
+    let text = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let number = |name: &str| text(name).and_then(|v| v.parse::<i64>().ok());
+
+    let limit = number("x-ratelimit-limit")?;
+    let remaining = number("x-ratelimit-remaining")?;
+    Some(GitHubRateLimit {
+        account: account.to_string(),
+        resource: text("x-ratelimit-resource")?.to_string(),
+        limit,
+        remaining,
+        used: number("x-ratelimit-used").unwrap_or(limit - remaining),
+        reset: number("x-ratelimit-reset")?,
+    })
+}
+
+pub(crate) fn store_rate_limit(rate_limit: GitHubRateLimit) {
+    if let Ok(mut limits) = RATE_LIMITS.lock() {
+        limits.insert((rate_limit.account.clone(), rate_limit.resource.clone()), rate_limit);
+    }
+}
+
+// Secondary limits and 429s don't always come with budget headers
+pub(crate) fn mark_rate_limited(account: &str, resource: &str, until: i64) {
+    if let Ok(mut limits) = RATE_LIMITS.lock() {
+        let key = (account.to_string(), resource.to_string());
+        let entry = limits.entry(key).or_insert_with(|| GitHubRateLimit {
+            account: account.to_string(),
+            resource: resource.to_string(),
+            limit: 0,
+            remaining: 0,
+            used: 0,
+            reset: until,
+        });
+        entry.remaining = 0;
+        entry.reset = entry.reset.max(until);
+    }
+}
+
+pub(crate) fn rate_limited_until(account: &str, resource: &str, now: i64) -> Option<i64> {
+    let limits = RATE_LIMITS.lock().ok()?;
+    limits
+        .get(&(account.to_string(), resource.to_string()))
+        .filter(|l| l.remaining <= 0 && l.reset > now)
+        .map(|l| l.reset)
+}
+
+/// The last GitHub API budget seen for each account and resource.
+pub fn github_rate_limits() -> Vec<GitHubRateLimit> {
+    let mut limits: Vec<GitHubRateLimit> = RATE_LIMITS
+        .lock()
+        .map(|limits| limits.values().cloned().collect())
+        .unwrap_or_default();
+    limits.sort_by(|a, b| (&a.account, &a.resource).cmp(&(&b.account, &b.resource)));
+    limits
+}
+
+/// When GitHub data can be refreshed again, if any account's budget is used up.
+pub fn github_rate_limited_until(now: i64) -> Option<i64> {
+    github_rate_limits()
+        .into_iter()
+        .filter(|l| l.remaining <= 0 && l.reset > now)
+        .map(|l| l.reset)
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn reads_the_budget_from_rate_limit_headers() {
+        let rate_limit = rate_limit_from_headers(
+            "work",
+            &headers(&[
+                ("x-ratelimit-resource", "search"),
+                ("x-ratelimit-limit", "30"),
+                ("x-ratelimit-remaining", "4"),
+                ("x-ratelimit-used", "26"),
+                ("x-ratelimit-reset", "1770000060"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(rate_limit.account, "work");
+        assert_eq!(rate_limit.resource, "search");
+        assert_eq!((rate_limit.limit, rate_limit.remaining, rate_limit.used), (30, 4, 26));
+        assert_eq!(rate_limit.reset, 1_770_000_060);
+    }
+
+    #[test]
+    fn derives_used_when_the_header_is_missing() {
+        let rate_limit = rate_limit_from_headers(
+            "work",
+            &headers(&[
+                ("x-ratelimit-resource", "core"),
+                ("x-ratelimit-limit", "5000"),
+                ("x-ratelimit-remaining", "4990"),
+                ("x-ratelimit-reset", "1770000060"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(rate_limit.used, 10);
+    }
+
+    #[test]
+    fn ignores_incomplete_or_malformed_headers() {
+        assert!(rate_limit_from_headers("work", &HeaderMap::new()).is_none());
+        assert!(rate_limit_from_headers(
+            "work",
+            &headers(&[
+                ("x-ratelimit-resource", "core"),
+                ("x-ratelimit-limit", "5000"),
+                ("x-ratelimit-remaining", "many"),
+                ("x-ratelimit-reset", "1770000060"),
+            ]),
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn refuses_a_spent_budget_until_it_resets() {
+        mark_rate_limited("refuses-spent", "core", 1_770_000_060);
+
+        assert_eq!(rate_limited_until("refuses-spent", "core", 1_770_000_000), Some(1_770_000_060));
+        assert_eq!(rate_limited_until("refuses-spent", "core", 1_770_000_060), None);
+        assert_eq!(rate_limited_until("refuses-spent", "search", 1_770_000_000), None);
+    }
+}
@@ -0,0 +1,508 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Weekday};
+
+// Hard stop for pathological rules (e.g. FREQ=DAILY from decades ago with no COUNT/UNTIL)
+const MAX_PERIODS: u32 = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Until {
+    // UNTIL given as a UTC datetime (…Z), compared against occurrence timestamps
+    Utc(i64),
+    // UNTIL given as a DATE or floating DATE-TIME, compared in the event's own wall-clock time
+    Local(NaiveDateTime),
+}
+
+#[derive(Debug, Clone)]
+pub struct RecurrenceRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<Until>,
+    pub by_day: Vec<(Option<i32>, Weekday)>,
+    pub by_month_day: Vec<i32>,
+    pub by_month: Vec<u32>,
+    pub by_set_pos: Vec<i32>,
+    pub week_start: Weekday,
+}
+
+impl RecurrenceRule {
+    /// Parses an RRULE value such as `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;UNTIL=20250101T000000Z`.
+    /// Returns `None` for rules we cannot expand (missing or sub-daily FREQ).
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut freq = None;
+        let mut rule = RecurrenceRule {
+            freq: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+            by_set_pos: Vec::new(),
+            week_start: Weekday::Mon,
+        };
+
+        for part in value.split(';') {
+            let (key, val) = match part.split_once('=') {
+                Some(kv) => kv,
+                None => continue,
+            };
+
+            match key.trim().to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    freq = match val.trim().to_ascii_uppercase().as_str() {
+                        "DAILY" => Some(Frequency::Daily),
+                        "WEEKLY" => Some(Frequency::Weekly),
+                        "MONTHLY" => Some(Frequency::Monthly),
+                        "YEARLY" => Some(Frequency::Yearly),
+                        _ => None,
+                    }
+                }
+                "INTERVAL" => rule.interval = val.trim().parse().ok().filter(|i| *i > 0).unwrap_or(1),
+                "COUNT" => rule.count = val.trim().parse().ok(),
+                "UNTIL" => rule.until = parse_until(val.trim()),
+                "BYDAY" => rule.by_day = val.split(',').filter_map(parse_by_day).collect(),
+                "BYMONTHDAY" => rule.by_month_day = parse_int_list(val),
+                "BYMONTH" => {
+                    rule.by_month = parse_int_list(val)
+                        .into_iter()
+                        .filter(|m| (1..=12).contains(m))
+                        .map(|m| m as u32)
+                        .collect()
+                }
+                "BYSETPOS" => rule.by_set_pos = parse_int_list(val),
+                "WKST" => {
+                    if let Some(day) = parse_weekday(val.trim()) {
+                        rule.week_start = day;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        rule.freq = freq?;
+        Some(rule)
+    }
+
+    /// Expands the rule starting at `dtstart` and returns every occurrence (including
+    /// `dtstart` itself) whose timestamp is at or before `range_end`. COUNT and UNTIL are
+    /// honoured from the first occurrence, so callers filter the result to their window.
+    /// Occurrences keep their nominal wall-clock time; `to_timestamp` decides where ones in
+    /// a DST gap land.
+    pub fn expand<F>(&self, dtstart: NaiveDateTime, to_timestamp: F, range_end: i64) -> Vec<NaiveDateTime>
+    where
+        F: Fn(NaiveDateTime) -> i64,
+    {
+        let mut occurrences = Vec::new();
+        let mut emitted = 0u32;
+
+        // DTSTART is always the first instance of the set
+        let ts = to_timestamp(dtstart);
+        if ts > range_end || self.past_until(dtstart, ts) {
+            return occurrences;
+        }
+        occurrences.push(dtstart);
+        emitted += 1;
+
+        for period in 0..MAX_PERIODS {
+            let candidates = match self.period_candidates(dtstart, period) {
+                Some(candidates) => candidates,
+                None => break,
+            };
+
+            for candidate in candidates {
+                if candidate <= dtstart {
+                    continue;
+                }
+                if self.count.is_some_and(|count| emitted >= count) {
+                    return occurrences;
+                }
+
+                let ts = to_timestamp(candidate);
+                if ts > range_end || self.past_until(candidate, ts) {
+                    return occurrences;
+                }
+
+                occurrences.push(candidate);
+                emitted += 1;
+            }
+        }
+
+        occurrences
+    }
+
+    fn past_until(&self, candidate: NaiveDateTime, ts: i64) -> bool {
+        match self.until {
+            Some(Until::Utc(until)) => ts > until,
+            Some(Until::Local(until)) => candidate > until,
+            None => false,
+        }
+    }
+
+    // Candidate occurrences for the n-th period after DTSTART, sorted and with BYSETPOS applied.
+    // Returns None once the period runs past the representable calendar.
+    fn period_candidates(&self, dtstart: NaiveDateTime, period: u32) -> Option<Vec<NaiveDateTime>> {
+        let step = period.checked_mul(self.interval)?;
+        let start_date = dtstart.date();
+
+        let mut dates: Vec<NaiveDate> = match self.freq {
+            Frequency::Daily => {
+                let date = start_date.checked_add_signed(Duration::days(step as i64))?;
+                if self.matches_filters(date) {
+                    vec![date]
+                } else {
+                    Vec::new()
+                }
+            }
+            Frequency::Weekly => {
+                let offset = (start_date.weekday().num_days_from_monday() + 7
+                    - self.week_start.num_days_from_monday())
+                    % 7;
+                let week_start = start_date
+                    .checked_sub_signed(Duration::days(offset as i64))?
+                    .checked_add_signed(Duration::weeks(step as i64))?;
+
+                let weekdays: Vec<Weekday> = if self.by_day.is_empty() {
+                    vec![start_date.weekday()]
+                } else {
+                    self.by_day.iter().map(|(_, day)| *day).collect()
+                };
+
+                weekdays
+                    .into_iter()
+                    .filter_map(|day| {
+                        let days = (day.num_days_from_monday() + 7
+                            - self.week_start.num_days_from_monday())
+                            % 7;
+                        week_start.checked_add_signed(Duration::days(days as i64))
+                    })
+                    .filter(|date| self.by_month.is_empty() || self.by_month.contains(&date.month()))
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let months = start_date.month0() as i64 + step as i64;
+                let year = start_date.year() as i64 + months.div_euclid(12);
+                let month = months.rem_euclid(12) as u32 + 1;
+                if year > 9999 {
+                    return None;
+                }
+                if !self.by_month.is_empty() && !self.by_month.contains(&month) {
+                    Vec::new()
+                } else {
+                    self.month_candidates(year as i32, month, start_date.day())
+                }
+            }
+            Frequency::Yearly => {
+                let year = start_date.year() as i64 + step as i64;
+                if year > 9999 {
+                    return None;
+                }
+                let year = year as i32;
+
+                if !self.by_day.is_empty() && self.by_month.is_empty() && self.by_month_day.is_empty() {
+                    // e.g. BYDAY=20MO: ordinals count within the whole year
+                    let days: Vec<NaiveDate> = NaiveDate::from_ymd_opt(year, 1, 1)?
+                        .iter_days()
+                        .take_while(|d| d.year() == year)
+                        .collect();
+                    select_by_day(&days, &self.by_day)
+                } else {
+                    let months = if self.by_month.is_empty() {
+                        vec![start_date.month()]
+                    } else {
+                        self.by_month.clone()
+                    };
+                    months
+                        .into_iter()
+                        .flat_map(|month| self.month_candidates(year, month, start_date.day()))
+                        .collect()
+                }
+            }
+        };
+
+        dates.sort();
+        dates.dedup();
+
+        if !self.by_set_pos.is_empty() {
+            let len = dates.len() as i32;
+            let mut selected: Vec<NaiveDate> = self
+                .by_set_pos
+                .iter()
+                .filter_map(|pos| {
+                    let index = if *pos > 0 { pos - 1 } else { len + pos };
+                    (0..len).contains(&index).then(|| dates[index as usize])
+                })
+                .collect();
+            selected.sort();
+            selected.dedup();
+            dates = selected;
+        }
+
+        Some(dates.into_iter().map(|date| date.and_time(dtstart.time())).collect())
+    }
+
+    fn month_candidates(&self, year: i32, month: u32, default_day: u32) -> Vec<NaiveDate> {
+        let first = match NaiveDate::from_ymd_opt(year, month, 1) {
+            Some(first) => first,
+            None => return Vec::new(),
+        };
+        let days: Vec<NaiveDate> = first.iter_days().take_while(|d| d.month() == month).collect();
+        let days_in_month = days.len() as i32;
+
+        if !self.by_month_day.is_empty() {
+            self.by_month_day
+                .iter()
+                .filter_map(|day| {
+                    let day = if *day < 0 { days_in_month + day + 1 } else { *day };
+                    (1..=days_in_month).contains(&day).then(|| days[(day - 1) as usize])
+                })
+                .filter(|date| {
+                    self.by_day.is_empty() || self.by_day.iter().any(|(_, wd)| *wd == date.weekday())
+                })
+                .collect()
+        } else if !self.by_day.is_empty() {
+            select_by_day(&days, &self.by_day)
+        } else {
+            days.get(default_day as usize - 1).copied().into_iter().collect()
+        }
+    }
+
+    fn matches_filters(&self, date: NaiveDate) -> bool {
+        if !self.by_month.is_empty() && !self.by_month.contains(&date.month()) {
+            return false;
+        }
+        if !self.by_day.is_empty() && !self.by_day.iter().any(|(_, wd)| *wd == date.weekday()) {
+            return false;
+        }
+        if !self.by_month_day.is_empty() {
+            let days_in_month = days_in_month(date) as i32;
+            let day = date.day() as i32;
+            if !self
+                .by_month_day
+                .iter()
+                .any(|d| *d == day || (*d < 0 && days_in_month + d + 1 == day))
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+// Picks days matching BYDAY entries, honouring ordinals like 2TU or -1FR within `days`
+fn select_by_day(days: &[NaiveDate], by_day: &[(Option<i32>, Weekday)]) -> Vec<NaiveDate> {
+    let mut selected = Vec::new();
+
+    for (ordinal, weekday) in by_day {
+        let matching: Vec<NaiveDate> = days.iter().copied().filter(|d| d.weekday() == *weekday).collect();
+        match ordinal {
+            None | Some(0) => selected.extend(matching),
+            Some(n) => {
+                let len = matching.len() as i32;
+                let index = if *n > 0 { n - 1 } else { len + n };
+                if (0..len).contains(&index) {
+                    selected.push(matching[index as usize]);
+                }
+            }
+        }
+    }
+
+    selected
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|next| next.pred_opt())
+        .map(|last| last.day())
+        .unwrap_or(31)
+}
+
+fn parse_until(value: &str) -> Option<Until> {
+    if let Some(stripped) = value.strip_suffix('Z') {
+        let datetime = NaiveDateTime::parse_from_str(stripped, "%Y%m%dT%H%M%S").ok()?;
+        Some(Until::Utc(datetime.and_utc().timestamp()))
+    } else if value.len() == 8 {
+        // A DATE UNTIL is inclusive of the whole day
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        Some(Until::Local(date.and_hms_opt(23, 59, 59)?))
+    } else {
+        let datetime = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+        Some(Until::Local(datetime))
+    }
+}
+
+fn parse_by_day(value: &str) -> Option<(Option<i32>, Weekday)> {
+    let value = value.trim();
+    // Splitting by bytes below is only safe for ASCII, which every valid BYDAY value is
+    if value.len() < 2 || !value.is_ascii() {
+        return None;
+    }
+    let (ordinal, day) = value.split_at(value.len() - 2);
+    let weekday = parse_weekday(day)?;
+    let ordinal = if ordinal.is_empty() {
+        None
+    } else {
+        Some(ordinal.trim_start_matches('+').parse().ok()?)
+    };
+    Some((ordinal, weekday))
+}
+
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    match value.to_ascii_uppercase().as_str() {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn parse_int_list(value: &str) -> Vec<i32> {
+    value
+        .split(',')
+        .filter_map(|v| v.trim().trim_start_matches('+').parse().ok())
+        .filter(|v| *v != 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Timelike};
+    use chrono_tz::Europe::Berlin;
+    use crate::services::ResolvedZone;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    fn utc(dt: NaiveDateTime) -> i64 {
+        dt.and_utc().timestamp()
+    }
+
+    fn expand(rule: &str, dtstart: NaiveDateTime, range_end: NaiveDateTime) -> Vec<NaiveDateTime> {
+        RecurrenceRule::parse(rule)
+            .unwrap()
+            .expand(dtstart, utc, range_end.and_utc().timestamp())
+    }
+
+    #[test]
+    fn weekly_by_day_stops_after_count() {
+        // Monday 2026-01-05
+        let occurrences = expand("FREQ=WEEKLY;BYDAY=MO,WE;COUNT=5", at(2026, 1, 5, 9), at(2027, 1, 1, 0));
+        assert_eq!(
+            occurrences,
+            vec![
+                at(2026, 1, 5, 9),
+                at(2026, 1, 7, 9),
+                at(2026, 1, 12, 9),
+                at(2026, 1, 14, 9),
+                at(2026, 1, 19, 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn monthly_last_friday() {
+        let occurrences = expand("FREQ=MONTHLY;BYDAY=-1FR", at(2026, 1, 30, 16), at(2026, 5, 1, 0));
+        assert_eq!(
+            occurrences,
+            vec![at(2026, 1, 30, 16), at(2026, 2, 27, 16), at(2026, 3, 27, 16), at(2026, 4, 24, 16)]
+        );
+    }
+
+    #[test]
+    fn monthly_last_weekday_by_set_pos() {
+        let occurrences = expand(
+            "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1;COUNT=3",
+            at(2026, 1, 30, 9),
+            at(2027, 1, 1, 0),
+        );
+        // 2026-05-31 is a Sunday
+        assert_eq!(occurrences, vec![at(2026, 1, 30, 9), at(2026, 2, 27, 9), at(2026, 3, 31, 9)]);
+    }
+
+    #[test]
+    fn until_is_inclusive_and_wins_over_range() {
+        let occurrences = expand("FREQ=DAILY;UNTIL=20260103", at(2026, 1, 1, 9), at(2027, 1, 1, 0));
+        assert_eq!(occurrences, vec![at(2026, 1, 1, 9), at(2026, 1, 2, 9), at(2026, 1, 3, 9)]);
+
+        let occurrences = expand("FREQ=DAILY;UNTIL=20260102T080000Z", at(2026, 1, 1, 9), at(2027, 1, 1, 0));
+        assert_eq!(occurrences, vec![at(2026, 1, 1, 9)]);
+    }
+
+    #[test]
+    fn keeps_wall_clock_time_across_dst() {
+        // Europe/Berlin switches to summer time on 2026-03-29
+        let rule = RecurrenceRule::parse("FREQ=WEEKLY;COUNT=3").unwrap();
+        let to_timestamp = |dt: NaiveDateTime| Berlin.from_local_datetime(&dt).unwrap().timestamp();
+        let range_end = at(2027, 1, 1, 0).and_utc().timestamp();
+
+        let occurrences = rule.expand(at(2026, 3, 22, 9), to_timestamp, range_end);
+        assert_eq!(occurrences, vec![at(2026, 3, 22, 9), at(2026, 3, 29, 9), at(2026, 4, 5, 9)]);
+
+        // 09:00 CET is 08:00 UTC, 09:00 CEST is 07:00 UTC
+        let utc_hours: Vec<u32> = occurrences
+            .iter()
+            .map(|dt| Berlin.from_local_datetime(dt).unwrap().naive_utc().hour())
+            .collect();
+        assert_eq!(utc_hours, vec![8, 7, 7]);
+    }
+
+    #[test]
+    fn counts_instances_in_the_dst_gap() {
+        // 02:30 doesn't exist in Berlin on 2026-03-29
+        let zone = ResolvedZone::Iana(Berlin);
+        let rule = RecurrenceRule::parse("FREQ=DAILY;COUNT=3").unwrap();
+        let range_end = at(2027, 1, 1, 0).and_utc().timestamp();
+
+        let start = at(2026, 3, 28, 2) + Duration::minutes(30);
+        let occurrences = rule.expand(start, |dt| zone.to_timestamp(dt), range_end);
+        assert_eq!(occurrences, vec![start, start + Duration::days(1), start + Duration::days(2)]);
+
+        // The gap instance is shifted forward by the hour that was skipped: 03:30 CEST
+        let shifted = zone.to_timestamp(occurrences[1]);
+        assert_eq!(shifted, utc(at(2026, 3, 29, 1) + Duration::minutes(30)));
+    }
+
+    #[test]
+    fn keeps_a_series_starting_in_the_dst_gap() {
+        let zone = ResolvedZone::Iana(Berlin);
+        let rule = RecurrenceRule::parse("FREQ=WEEKLY;COUNT=2").unwrap();
+        let range_end = at(2027, 1, 1, 0).and_utc().timestamp();
+
+        let start = at(2026, 3, 29, 2) + Duration::minutes(30);
+        let occurrences = rule.expand(start, |dt| zone.to_timestamp(dt), range_end);
+        assert_eq!(occurrences, vec![start, start + Duration::weeks(1)]);
+    }
+
+    #[test]
+    fn parses_by_day_ordinals() {
+        assert_eq!(parse_by_day("-1FR"), Some((Some(-1), Weekday::Fri)));
+        assert_eq!(parse_by_day("+2TU"), Some((Some(2), Weekday::Tue)));
+        assert_eq!(parse_by_day("su"), Some((None, Weekday::Sun)));
+        assert_eq!(parse_by_day("XX"), None);
+    }
+
+    #[test]
+    fn rejects_non_ascii_by_day() {
+        assert_eq!(parse_by_day("éa"), None);
+        assert_eq!(parse_by_day("1é"), None);
+        assert!(RecurrenceRule::parse("FREQ=WEEKLY;BYDAY=éa,MO").is_some());
+    }
+}
@@ -0,0 +1,462 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Weekday};
+use chrono_tz::Tz;
+use crate::models::{
+    BackToBackChain, CalendarConflict, CalendarEvent, CalendarTask, DaySchedule, Lookahead, MeetingReminder, TimeSlot,
+};
+use super::timezone::start_of_day;
+
+// Working hours that free time and focus blocks are measured against
+const WORK_DAY_START_HOUR: u32 = 9;
+const WORK_DAY_END_HOUR: u32 = 18;
+
+// Gaps shorter than this aren't worth planning around
+const MIN_FREE_SECONDS: i64 = 15 * 60;
+
+// Meetings separated by at most this much count as back-to-back
+const BACK_TO_BACK_SLACK_SECONDS: i64 = 5 * 60;
+
+/// Summarises today's meetings: load, free gaps within working hours, back-to-back chains
+/// and the longest focus block. All-day and declined events don't make the user busy.
+pub fn analyze_day(events: &[CalendarEvent], tz: Tz, now: i64) -> DaySchedule {
+    let (today, day_start, day_end) = day_bounds(tz, now);
+    let work_start = local_time(tz, today, WORK_DAY_START_HOUR).clamp(day_start, day_end);
+    let work_end = local_time(tz, today, WORK_DAY_END_HOUR).clamp(work_start, day_end);
+
+    let mut meetings: Vec<&CalendarEvent> = events
+        .iter()
+        .filter(|e| !e.all_day && !e.is_declined())
+        .filter(|e| e.start_time < day_end && e.end_time > day_start)
+        .collect();
+    meetings.sort_by_key(|e| (e.start_time, e.end_time));
+
+    let busy = merge_busy(&meetings, day_start, day_end);
+    let meeting_minutes = busy.iter().map(|(start, end)| end - start).sum::<i64>() / 60;
+
+    let free_gaps = free_gaps(&busy, work_start, work_end);
+    let longest_focus_block = free_gaps.iter().max_by_key(|gap| gap.end - gap.start).cloned();
+    let next_free = free_gaps
+        .iter()
+        .find(|gap| gap.end - now.max(gap.start) >= MIN_FREE_SECONDS)
+        .map(|gap| TimeSlot::new(now.max(gap.start), gap.end));
+
+    DaySchedule {
+        work_start,
+        work_end,
+        meeting_count: meetings.len(),
+        meeting_minutes,
+        back_to_back: back_to_back_chains(&meetings),
+        free_gaps,
+        longest_focus_block,
+        next_free,
+    }
+}
+
+/// Pairs of today's events that compete for the same time: overlapping meetings, and
+/// meetings scheduled while the user is marked out of office. Declined events are ignored.
+pub fn find_conflicts(events: &[CalendarEvent], tz: Tz, now: i64) -> Vec<CalendarConflict> {
+    let (_, day_start, day_end) = day_bounds(tz, now);
+
+    let mut relevant: Vec<&CalendarEvent> = events
+        .iter()
+        .filter(|e| !e.is_declined() && (!e.all_day || e.out_of_office))
+        .filter(|e| e.start_time < day_end && e.end_time > day_start)
+        .collect();
+    relevant.sort_by_key(|e| (e.start_time, e.end_time));
+
+    let mut conflicts = Vec::new();
+    for (i, first) in relevant.iter().enumerate() {
+        for second in &relevant[i + 1..] {
+            // Sorted by start, so nothing later can overlap `first` either
+            if second.start_time >= first.end_time {
+                break;
+            }
+
+            let kind = match (first.out_of_office, second.out_of_office) {
+                (false, false) => "double_booking",
+                (true, true) => continue,
+                _ => "out_of_office",
+            };
+
+            conflicts.push(CalendarConflict {
+                kind: kind.to_string(),
+                start: second.start_time.max(day_start),
+                end: first.end_time.min(second.end_time).min(day_end),
+                event_ids: vec![first.id.clone(), second.id.clone()],
+                summaries: vec![first.summary.clone(), second.summary.clone()],
+            });
+        }
+    }
+
+    conflicts
+}
+
+/// Reminders that fall due in (from, to]: each event's VALARM lead times, or `default_lead`
+/// seconds for events without any. All-day and declined events don't get reminders.
+pub fn due_reminders(events: &[CalendarEvent], default_lead: Option<i64>, from: i64, to: i64) -> Vec<MeetingReminder> {
+    let mut reminders = Vec::new();
+
+    for event in events.iter().filter(|e| !e.all_day && !e.is_declined()) {
+        let leads = if event.reminder_offsets.is_empty() {
+            default_lead.into_iter().collect()
+        } else {
+            event.reminder_offsets.clone()
+        };
+
+        for lead in leads {
+            let remind_at = event.start_time - lead;
+            if remind_at > from && remind_at <= to {
+                reminders.push(MeetingReminder {
+                    event: event.clone(),
+                    remind_at,
+                });
+            }
+        }
+    }
+
+    reminders.sort_by_key(|r| r.remind_at);
+    reminders
+}
+
+/// Start and end of the next working day after `now`: Monday when asked on a Friday or at
+/// the weekend.
+pub fn next_working_day(tz: Tz, now: i64) -> (i64, i64) {
+    let (today, _, _) = day_bounds(tz, now);
+    let mut day = today + Duration::days(1);
+    while matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+        day += Duration::days(1);
+    }
+    (start_of_day(tz, day), start_of_day(tz, day + Duration::days(1)))
+}
+
+/// Range the lookahead lists deadlines for: from tomorrow to the end of the week (Sunday),
+/// stretched to cover the next working day when that falls after it.
+pub fn deadline_range(tz: Tz, now: i64) -> (i64, i64) {
+    let (today, _, tomorrow_start) = day_bounds(tz, now);
+    let days_left = 7 - today.weekday().num_days_from_monday() as i64;
+    let week_end = start_of_day(tz, today + Duration::days(days_left));
+    (tomorrow_start, week_end.max(next_working_day(tz, now).1))
+}
+
+/// Builds the lookahead from the events overlapping the next working day and the tasks due
+/// in the deadline range. All-day events, declined invitations and absences aren't meetings.
+pub fn build_lookahead(next_day: (i64, i64), events: &[CalendarEvent], deadlines: Vec<CalendarTask>) -> Lookahead {
+    let (day_start, day_end) = next_day;
+
+    let mut meetings: Vec<&CalendarEvent> = events
+        .iter()
+        .filter(|e| !e.all_day && !e.is_declined() && !e.out_of_office)
+        .filter(|e| e.start_time >= day_start && e.start_time < day_end)
+        .collect();
+    meetings.sort_by_key(|e| (e.start_time, e.end_time));
+
+    Lookahead {
+        next_day_start: day_start,
+        first_meeting: meetings.first().map(|e| (*e).clone()),
+        meeting_count: meetings.len(),
+        deadlines,
+    }
+}
+
+// The user's local date at `now`, with the timestamps it starts and ends at
+fn day_bounds(tz: Tz, now: i64) -> (NaiveDate, i64, i64) {
+    let today = DateTime::from_timestamp(now, 0).unwrap_or_default().with_timezone(&tz).date_naive();
+    (today, start_of_day(tz, today), start_of_day(tz, today + Duration::days(1)))
+}
+
+fn local_time(tz: Tz, date: NaiveDate, hour: u32) -> i64 {
+    let time = NaiveTime::from_hms_opt(hour, 0, 0).unwrap_or(NaiveTime::MIN);
+    tz.from_local_datetime(&date.and_time(time))
+        .earliest()
+        .map(|dt| dt.timestamp())
+        .unwrap_or_else(|| start_of_day(tz, date) + hour as i64 * 3600)
+}
+
+// Union of the meetings' time ranges, clipped to the day; expects meetings sorted by start
+fn merge_busy(meetings: &[&CalendarEvent], day_start: i64, day_end: i64) -> Vec<(i64, i64)> {
+    let mut busy: Vec<(i64, i64)> = Vec::new();
+
+    for event in meetings {
+        let start = event.start_time.max(day_start);
+        let end = event.end_time.min(day_end);
+        match busy.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => busy.push((start, end)),
+        }
+    }
+
+    busy
+}
+
+fn free_gaps(busy: &[(i64, i64)], work_start: i64, work_end: i64) -> Vec<TimeSlot> {
+    let mut gaps = Vec::new();
+    let mut cursor = work_start;
+
+    for &(start, end) in busy {
+        if start > cursor {
+            gaps.push((cursor, start.min(work_end)));
+        }
+        cursor = cursor.max(end);
+        if cursor >= work_end {
+            break;
+        }
+    }
+    if cursor < work_end {
+        gaps.push((cursor, work_end));
+    }
+
+    gaps.into_iter()
+        .filter(|(start, end)| end - start >= MIN_FREE_SECONDS)
+        .map(|(start, end)| TimeSlot::new(start, end))
+        .collect()
+}
+
+// Runs of two or more meetings with no more than the slack between them; expects meetings sorted by start
+fn back_to_back_chains(meetings: &[&CalendarEvent]) -> Vec<BackToBackChain> {
+    let mut chains = Vec::new();
+    let mut current: Option<BackToBackChain> = None;
+
+    for event in meetings {
+        match current {
+            Some(ref mut chain) if event.start_time - chain.end <= BACK_TO_BACK_SLACK_SECONDS => {
+                chain.end = chain.end.max(event.end_time);
+                chain.event_ids.push(event.id.clone());
+            }
+            _ => {
+                chains.extend(current.take().filter(|c| c.event_ids.len() > 1));
+                current = Some(BackToBackChain {
+                    start: event.start_time,
+                    end: event.end_time,
+                    event_ids: vec![event.id.clone()],
+                });
+            }
+        }
+    }
+    chains.extend(current.filter(|c| c.event_ids.len() > 1));
+
+    chains
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::Europe::Berlin;
+
+    // Days of March 2026 in Berlin; the 11th is a Wednesday
+    fn at(day: u32, hour: u32, minute: u32) -> i64 {
+        Berlin.with_ymd_and_hms(2026, 3, day, hour, minute, 0).unwrap().timestamp()
+    }
+
+    fn event(id: &str, start: i64, end: i64) -> CalendarEvent {
+        CalendarEvent {
+            id: id.to_string(),
+            source: "work".to_string(),
+            summary: id.to_string(),
+            description: None,
+            location: None,
+            start_time: start,
+            end_time: end,
+            all_day: false,
+            html_link: None,
+            join_url: None,
+            conference_provider: None,
+            organizer: None,
+            attendees: Vec::new(),
+            my_status: None,
+            out_of_office: false,
+            reminder_offsets: Vec::new(),
+            color: None,
+            is_now: false,
+            is_soon: false,
+        }
+    }
+
+    fn slots(slots: &[TimeSlot]) -> Vec<(i64, i64)> {
+        slots.iter().map(|s| (s.start, s.end)).collect()
+    }
+
+    #[test]
+    fn finds_free_gaps_within_working_hours() {
+        let events = [
+            event("standup", at(11, 10, 0), at(11, 11, 0)),
+            event("review", at(11, 11, 0), at(11, 11, 30)),
+            event("lunch talk", at(11, 13, 0), at(11, 14, 0)),
+            event("offsite dinner", at(11, 17, 50), at(11, 19, 0)),
+        ];
+        let day = analyze_day(&events, Berlin, at(11, 8, 0));
+
+        assert_eq!((day.work_start, day.work_end), (at(11, 9, 0), at(11, 18, 0)));
+        assert_eq!(day.meeting_count, 4);
+        assert_eq!(day.meeting_minutes, 90 + 60 + 70);
+        assert_eq!(
+            slots(&day.free_gaps),
+            vec![
+                (at(11, 9, 0), at(11, 10, 0)),
+                (at(11, 11, 30), at(11, 13, 0)),
+                (at(11, 14, 0), at(11, 17, 50)),
+            ]
+        );
+        assert_eq!(day.longest_focus_block.map(|s| s.duration_minutes), Some(230));
+        assert_eq!(day.next_free.map(|s| (s.start, s.end)), Some((at(11, 9, 0), at(11, 10, 0))));
+
+        assert_eq!(day.back_to_back.len(), 1);
+        assert_eq!(day.back_to_back[0].event_ids, vec!["standup", "review"]);
+        assert_eq!((day.back_to_back[0].start, day.back_to_back[0].end), (at(11, 10, 0), at(11, 11, 30)));
+    }
+
+    #[test]
+    fn drops_gaps_shorter_than_fifteen_minutes() {
+        let events = [
+            event("a", at(11, 9, 0), at(11, 10, 0)),
+            event("b", at(11, 10, 10), at(11, 12, 0)),
+            event("c", at(11, 12, 15), at(11, 18, 0)),
+        ];
+
+        let day = analyze_day(&events, Berlin, at(11, 8, 0));
+        assert_eq!(slots(&day.free_gaps), vec![(at(11, 12, 0), at(11, 12, 15))]);
+
+        // Ten minutes into the only gap, what's left of it is too short
+        let day = analyze_day(&events, Berlin, at(11, 12, 5));
+        assert!(day.next_free.is_none());
+        let day = analyze_day(&events, Berlin, at(11, 11, 0));
+        assert_eq!(day.next_free.map(|s| s.duration_minutes), Some(15));
+    }
+
+    #[test]
+    fn all_day_and_declined_events_leave_the_day_free() {
+        let mut holiday = event("holiday", at(11, 0, 0), at(12, 0, 0));
+        holiday.all_day = true;
+        let mut declined = event("declined", at(11, 10, 0), at(11, 12, 0));
+        declined.my_status = Some("declined".to_string());
+
+        let day = analyze_day(&[holiday, declined], Berlin, at(11, 8, 0));
+        assert_eq!(day.meeting_count, 0);
+        assert_eq!(day.meeting_minutes, 0);
+        assert_eq!(slots(&day.free_gaps), vec![(at(11, 9, 0), at(11, 18, 0))]);
+        assert!(day.back_to_back.is_empty());
+    }
+
+    #[test]
+    fn clips_events_crossing_midnight_to_today() {
+        let events = [
+            event("night shift", at(10, 23, 0), at(11, 9, 30)),
+            event("release", at(11, 17, 0), at(12, 1, 0)),
+            event("yesterday", at(10, 10, 0), at(10, 11, 0)),
+        ];
+        let day = analyze_day(&events, Berlin, at(11, 8, 0));
+
+        assert_eq!(day.meeting_count, 2);
+        assert_eq!(day.meeting_minutes, 9 * 60 + 30 + 7 * 60);
+        assert_eq!(slots(&day.free_gaps), vec![(at(11, 9, 30), at(11, 17, 0))]);
+    }
+
+    #[test]
+    fn next_working_day_skips_the_weekend() {
+        // Wednesday, Friday, Saturday and Sunday
+        assert_eq!(next_working_day(Berlin, at(11, 15, 0)), (at(12, 0, 0), at(13, 0, 0)));
+        assert_eq!(next_working_day(Berlin, at(13, 15, 0)), (at(16, 0, 0), at(17, 0, 0)));
+        assert_eq!(next_working_day(Berlin, at(14, 15, 0)), (at(16, 0, 0), at(17, 0, 0)));
+        assert_eq!(next_working_day(Berlin, at(15, 23, 59)), (at(16, 0, 0), at(17, 0, 0)));
+    }
+
+    #[test]
+    fn deadline_range_runs_to_the_end_of_the_week() {
+        // From Wednesday: Thursday through Sunday
+        assert_eq!(deadline_range(Berlin, at(11, 15, 0)), (at(12, 0, 0), at(16, 0, 0)));
+        // From Friday and Sunday the next working day, Monday, is after the week's end
+        assert_eq!(deadline_range(Berlin, at(13, 15, 0)), (at(14, 0, 0), at(17, 0, 0)));
+        assert_eq!(deadline_range(Berlin, at(15, 9, 0)), (at(16, 0, 0), at(17, 0, 0)));
+    }
+
+    #[test]
+    fn lookahead_counts_meetings_on_the_next_working_day() {
+        let mut absence = event("vacation", at(16, 0, 0), at(17, 0, 0));
+        absence.out_of_office = true;
+        let events = [
+            event("planning", at(16, 11, 0), at(16, 12, 0)),
+            event("standup", at(16, 9, 30), at(16, 9, 45)),
+            event("friday", at(13, 9, 30), at(13, 9, 45)),
+            absence,
+        ];
+
+        let lookahead = build_lookahead(next_working_day(Berlin, at(13, 15, 0)), &events, Vec::new());
+        assert_eq!(lookahead.next_day_start, at(16, 0, 0));
+        assert_eq!(lookahead.meeting_count, 2);
+        assert_eq!(lookahead.first_meeting.map(|e| e.id), Some("standup".to_string()));
+    }
+
+    fn conflict_pairs(conflicts: &[CalendarConflict]) -> Vec<(&str, Vec<&str>, i64, i64)> {
+        conflicts
+            .iter()
+            .map(|c| (c.kind.as_str(), c.event_ids.iter().map(String::as_str).collect(), c.start, c.end))
+            .collect()
+    }
+
+    #[test]
+    fn reports_overlapping_meetings() {
+        let events = [
+            event("a", at(11, 10, 0), at(11, 11, 0)),
+            event("b", at(11, 10, 30), at(11, 11, 30)),
+            // Touching isn't overlapping
+            event("c", at(11, 11, 30), at(11, 12, 0)),
+        ];
+
+        let conflicts = find_conflicts(&events, Berlin, at(11, 8, 0));
+        assert_eq!(
+            conflict_pairs(&conflicts),
+            vec![("double_booking", vec!["a", "b"], at(11, 10, 30), at(11, 11, 0))]
+        );
+        assert_eq!(conflicts[0].summaries, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn ignores_declined_meetings() {
+        let mut declined = event("declined", at(11, 10, 0), at(11, 11, 0));
+        declined.my_status = Some("declined".to_string());
+        let events = [event("a", at(11, 10, 0), at(11, 11, 0)), declined];
+
+        assert!(find_conflicts(&events, Berlin, at(11, 8, 0)).is_empty());
+    }
+
+    #[test]
+    fn reports_meetings_during_an_all_day_absence() {
+        let mut vacation = event("vacation", at(11, 0, 0), at(12, 0, 0));
+        vacation.all_day = true;
+        vacation.out_of_office = true;
+        let mut sick = vacation.clone();
+        sick.id = "sick".to_string();
+        let mut holiday = event("holiday", at(11, 0, 0), at(12, 0, 0));
+        holiday.all_day = true;
+
+        let events = [vacation, sick, holiday, event("1:1", at(11, 14, 0), at(11, 15, 0))];
+        let conflicts = find_conflicts(&events, Berlin, at(11, 8, 0));
+
+        // Two absences don't conflict with each other, and plain all-day events are ignored
+        assert_eq!(
+            conflict_pairs(&conflicts),
+            vec![
+                ("out_of_office", vec!["vacation", "1:1"], at(11, 14, 0), at(11, 15, 0)),
+                ("out_of_office", vec!["sick", "1:1"], at(11, 14, 0), at(11, 15, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_looking_past_a_short_meeting() {
+        // Unsorted input; "a" stops looking at "c", but the long "b" still overlaps it
+        let events = [
+            event("c", at(11, 10, 0), at(11, 11, 0)),
+            event("b", at(11, 9, 30), at(11, 12, 0)),
+            event("a", at(11, 9, 0), at(11, 10, 0)),
+            event("tomorrow", at(12, 10, 0), at(12, 11, 0)),
+        ];
+
+        let conflicts = find_conflicts(&events, Berlin, at(11, 8, 0));
+        assert_eq!(
+            conflict_pairs(&conflicts),
+            vec![
+                ("double_booking", vec!["a", "b"], at(11, 9, 30), at(11, 10, 0)),
+                ("double_booking", vec!["b", "c"], at(11, 10, 0), at(11, 11, 0)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,522 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone};
+use chrono_tz::Tz;
+use ical::parser::ical::component::IcalTimeZone;
+use super::recurrence::RecurrenceRule;
+
+// Years of transitions expanded beyond the one asked for, so nearby conversions reuse them
+const TRANSITION_CACHE_YEARS: i32 = 10;
+
+// Windows time zone names (as found in Outlook/Exchange exports) to IANA, per CLDR windowsZones.xml
+const WINDOWS_ZONES: &[(&str, &str)] = &[
+    ("Dateline Standard Time", "Etc/GMT+12"),
+    ("UTC-11", "Etc/GMT+11"),
+    ("Aleutian Standard Time", "America/Adak"),
+    ("Hawaiian Standard Time", "Pacific/Honolulu"),
+    ("Marquesas Standard Time", "Pacific/Marquesas"),
+    ("Alaskan Standard Time", "America/Anchorage"),
+    ("UTC-09", "Etc/GMT+9"),
+    ("Pacific Standard Time (Mexico)", "America/Tijuana"),
+    ("UTC-08", "Etc/GMT+8"),
+    ("Pacific Standard Time", "America/Los_Angeles"),
+    ("US Mountain Standard Time", "America/Phoenix"),
+    ("Mountain Standard Time (Mexico)", "America/Mazatlan"),
+    ("Mountain Standard Time", "America/Denver"),
+    ("Yukon Standard Time", "America/Whitehorse"),
+    ("Central America Standard Time", "America/Guatemala"),
+    ("Central Standard Time", "America/Chicago"),
+    ("Easter Island Standard Time", "Pacific/Easter"),
+    ("Central Standard Time (Mexico)", "America/Mexico_City"),
+    ("Canada Central Standard Time", "America/Regina"),
+    ("SA Pacific Standard Time", "America/Bogota"),
+    ("Eastern Standard Time (Mexico)", "America/Cancun"),
+    ("Eastern Standard Time", "America/New_York"),
+    ("Haiti Standard Time", "America/Port-au-Prince"),
+    ("Cuba Standard Time", "America/Havana"),
+    ("US Eastern Standard Time", "America/Indiana/Indianapolis"),
+    ("Turks And Caicos Standard Time", "America/Grand_Turk"),
+    ("Paraguay Standard Time", "America/Asuncion"),
+    ("Atlantic Standard Time", "America/Halifax"),
+    ("Venezuela Standard Time", "America/Caracas"),
+    ("Central Brazilian Standard Time", "America/Cuiaba"),
+    ("SA Western Standard Time", "America/La_Paz"),
+    ("Pacific SA Standard Time", "America/Santiago"),
+    ("Newfoundland Standard Time", "America/St_Johns"),
+    ("Tocantins Standard Time", "America/Araguaina"),
+    ("E. South America Standard Time", "America/Sao_Paulo"),
+    ("SA Eastern Standard Time", "America/Cayenne"),
+    ("Argentina Standard Time", "America/Argentina/Buenos_Aires"),
+    ("Greenland Standard Time", "America/Nuuk"),
+    ("Montevideo Standard Time", "America/Montevideo"),
+    ("Magallanes Standard Time", "America/Punta_Arenas"),
+    ("Saint Pierre Standard Time", "America/Miquelon"),
+    ("Bahia Standard Time", "America/Bahia"),
+    ("UTC-02", "Etc/GMT+2"),
+    ("Mid-Atlantic Standard Time", "Etc/GMT+2"),
+    ("Azores Standard Time", "Atlantic/Azores"),
+    ("Cape Verde Standard Time", "Atlantic/Cape_Verde"),
+    ("UTC", "Etc/UTC"),
+    ("Coordinated Universal Time", "Etc/UTC"),
+    ("GMT Standard Time", "Europe/London"),
+    ("Greenwich Standard Time", "Atlantic/Reykjavik"),
+    ("Sao Tome Standard Time", "Africa/Sao_Tome"),
+    ("Morocco Standard Time", "Africa/Casablanca"),
+    ("W. Europe Standard Time", "Europe/Berlin"),
+    ("Central Europe Standard Time", "Europe/Budapest"),
+    ("Romance Standard Time", "Europe/Paris"),
+    ("Central European Standard Time", "Europe/Warsaw"),
+    ("W. Central Africa Standard Time", "Africa/Lagos"),
+    ("Jordan Standard Time", "Asia/Amman"),
+    ("GTB Standard Time", "Europe/Bucharest"),
+    ("Middle East Standard Time", "Asia/Beirut"),
+    ("Egypt Standard Time", "Africa/Cairo"),
+    ("E. Europe Standard Time", "Europe/Chisinau"),
+    ("Syria Standard Time", "Asia/Damascus"),
+    ("West Bank Standard Time", "Asia/Hebron"),
+    ("South Africa Standard Time", "Africa/Johannesburg"),
+    ("FLE Standard Time", "Europe/Kyiv"),
+    ("Israel Standard Time", "Asia/Jerusalem"),
+    ("South Sudan Standard Time", "Africa/Juba"),
+    ("Kaliningrad Standard Time", "Europe/Kaliningrad"),
+    ("Sudan Standard Time", "Africa/Khartoum"),
+    ("Libya Standard Time", "Africa/Tripoli"),
+    ("Namibia Standard Time", "Africa/Windhoek"),
+    ("Arabic Standard Time", "Asia/Baghdad"),
+    ("Turkey Standard Time", "Europe/Istanbul"),
+    ("Arab Standard Time", "Asia/Riyadh"),
+    ("Belarus Standard Time", "Europe/Minsk"),
+    ("Russian Standard Time", "Europe/Moscow"),
+    ("E. Africa Standard Time", "Africa/Nairobi"),
+    ("Volgograd Standard Time", "Europe/Volgograd"),
+    ("Iran Standard Time", "Asia/Tehran"),
+    ("Arabian Standard Time", "Asia/Dubai"),
+    ("Astrakhan Standard Time", "Europe/Astrakhan"),
+    ("Azerbaijan Standard Time", "Asia/Baku"),
+    ("Russia Time Zone 3", "Europe/Samara"),
+    ("Mauritius Standard Time", "Indian/Mauritius"),
+    ("Saratov Standard Time", "Europe/Saratov"),
+    ("Georgian Standard Time", "Asia/Tbilisi"),
+    ("Caucasus Standard Time", "Asia/Yerevan"),
+    ("Afghanistan Standard Time", "Asia/Kabul"),
+    ("West Asia Standard Time", "Asia/Tashkent"),
+    ("Ekaterinburg Standard Time", "Asia/Yekaterinburg"),
+    ("Pakistan Standard Time", "Asia/Karachi"),
+    ("Qyzylorda Standard Time", "Asia/Qyzylorda"),
+    ("India Standard Time", "Asia/Kolkata"),
+    ("Sri Lanka Standard Time", "Asia/Colombo"),
+    ("Nepal Standard Time", "Asia/Kathmandu"),
+    ("Central Asia Standard Time", "Asia/Almaty"),
+    ("Bangladesh Standard Time", "Asia/Dhaka"),
+    ("Omsk Standard Time", "Asia/Omsk"),
+    ("Myanmar Standard Time", "Asia/Yangon"),
+    ("SE Asia Standard Time", "Asia/Bangkok"),
+    ("Altai Standard Time", "Asia/Barnaul"),
+    ("W. Mongolia Standard Time", "Asia/Hovd"),
+    ("North Asia Standard Time", "Asia/Krasnoyarsk"),
+    ("N. Central Asia Standard Time", "Asia/Novosibirsk"),
+    ("Tomsk Standard Time", "Asia/Tomsk"),
+    ("China Standard Time", "Asia/Shanghai"),
+    ("North Asia East Standard Time", "Asia/Irkutsk"),
+    ("Singapore Standard Time", "Asia/Singapore"),
+    ("W. Australia Standard Time", "Australia/Perth"),
+    ("Taipei Standard Time", "Asia/Taipei"),
+    ("Ulaanbaatar Standard Time", "Asia/Ulaanbaatar"),
+    ("Aus Central W. Standard Time", "Australia/Eucla"),
+    ("Transbaikal Standard Time", "Asia/Chita"),
+    ("Tokyo Standard Time", "Asia/Tokyo"),
+    ("North Korea Standard Time", "Asia/Pyongyang"),
+    ("Korea Standard Time", "Asia/Seoul"),
+    ("Yakutsk Standard Time", "Asia/Yakutsk"),
+    ("Cen. Australia Standard Time", "Australia/Adelaide"),
+    ("AUS Central Standard Time", "Australia/Darwin"),
+    ("E. Australia Standard Time", "Australia/Brisbane"),
+    ("AUS Eastern Standard Time", "Australia/Sydney"),
+    ("West Pacific Standard Time", "Pacific/Port_Moresby"),
+    ("Tasmania Standard Time", "Australia/Hobart"),
+    ("Vladivostok Standard Time", "Asia/Vladivostok"),
+    ("Lord Howe Standard Time", "Australia/Lord_Howe"),
+    ("Bougainville Standard Time", "Pacific/Bougainville"),
+    ("Russia Time Zone 10", "Asia/Srednekolymsk"),
+    ("Magadan Standard Time", "Asia/Magadan"),
+    ("Norfolk Standard Time", "Pacific/Norfolk"),
+    ("Sakhalin Standard Time", "Asia/Sakhalin"),
+    ("Central Pacific Standard Time", "Pacific/Guadalcanal"),
+    ("Russia Time Zone 11", "Asia/Kamchatka"),
+    ("New Zealand Standard Time", "Pacific/Auckland"),
+    ("UTC+12", "Etc/GMT-12"),
+    ("Fiji Standard Time", "Pacific/Fiji"),
+    ("Kamchatka Standard Time", "Asia/Kamchatka"),
+    ("Chatham Islands Standard Time", "Pacific/Chatham"),
+    ("UTC+13", "Etc/GMT-13"),
+    ("Tonga Standard Time", "Pacific/Tongatapu"),
+    ("Samoa Standard Time", "Pacific/Apia"),
+    ("Line Islands Standard Time", "Pacific/Kiritimati"),
+];
+
+#[derive(Debug, Clone)]
+pub enum ResolvedZone {
+    Iana(Tz),
+    Custom(Arc<CustomZone>),
+}
+
+impl ResolvedZone {
+    /// Converts a wall-clock time in this zone to a UTC timestamp. Ambiguous times
+    /// (DST fall-back) resolve to the earlier instant, like `LocalResult::earliest`, and
+    /// nonexistent ones (spring-forward gap) are shifted forward by the gap (RFC 5545 §3.3.5).
+    pub fn to_timestamp(&self, local: NaiveDateTime) -> i64 {
+        match self {
+            ResolvedZone::Iana(tz) => match tz.from_local_datetime(&local).earliest() {
+                Some(dt) => dt.timestamp(),
+                None => {
+                    // Read with the offset before the gap; no zone is ahead of UTC by more than 14h
+                    let before = tz.offset_from_utc_datetime(&(local - Duration::hours(14))).fix();
+                    local.and_utc().timestamp() - before.local_minus_utc() as i64
+                }
+            },
+            ResolvedZone::Custom(zone) => zone.to_timestamp(local),
+        }
+    }
+}
+
+// One STANDARD or DAYLIGHT block of a VTIMEZONE
+#[derive(Debug)]
+struct Observance {
+    start: NaiveDateTime,
+    offset_from: i64,
+    offset_to: i64,
+    rrule: Option<RecurrenceRule>,
+    rdates: Vec<NaiveDateTime>,
+}
+
+// Onsets of every observance as (UTC instant, offset from then on), sorted, up to the end of `until_year`
+#[derive(Debug)]
+struct Transitions {
+    until_year: i32,
+    onsets: Vec<(i64, i64)>,
+}
+
+/// A VTIMEZONE whose TZID is not a known IANA or Windows name, evaluated from its own rules.
+#[derive(Debug)]
+pub struct CustomZone {
+    observances: Vec<Observance>,
+    // Observances start as early as 1601 in Outlook exports, so expanding their rules for
+    // every conversion would walk centuries of transitions each time
+    transitions: Mutex<Transitions>,
+}
+
+impl CustomZone {
+    fn from_ical(timezone: &IcalTimeZone) -> Option<Self> {
+        let observances: Vec<Observance> = timezone
+            .transitions
+            .iter()
+            .filter_map(|transition| {
+                let mut start = None;
+                let mut offset_from = None;
+                let mut offset_to = None;
+                let mut rrule = None;
+                let mut rdates = Vec::new();
+
+                for prop in &transition.properties {
+                    let value = match prop.value {
+                        Some(ref value) => value.as_str(),
+                        None => continue,
+                    };
+                    match prop.name.as_str() {
+                        "DTSTART" => start = parse_local(value),
+                        "TZOFFSETFROM" => offset_from = parse_offset(value),
+                        "TZOFFSETTO" => offset_to = parse_offset(value),
+                        "RRULE" => rrule = RecurrenceRule::parse(value),
+                        "RDATE" => rdates.extend(value.split(',').filter_map(parse_local)),
+                        _ => {}
+                    }
+                }
+
+                let offset_to = offset_to?;
+                Some(Observance {
+                    start: start?,
+                    offset_from: offset_from.unwrap_or(offset_to),
+                    offset_to,
+                    rrule,
+                    rdates,
+                })
+            })
+            .collect();
+
+        if observances.is_empty() {
+            None
+        } else {
+            Some(Self {
+                observances,
+                transitions: Mutex::new(Transitions {
+                    until_year: i32::MIN,
+                    onsets: Vec::new(),
+                }),
+            })
+        }
+    }
+
+    fn to_timestamp(&self, local: NaiveDateTime) -> i64 {
+        let local_ts = local.and_utc().timestamp();
+        let until_year = local.year() + 1;
+
+        // Try each offset the zone can have; the right one is the offset in effect at the
+        // instant it produces. Larger offsets come first so overlaps resolve to the earlier instant.
+        let mut offsets: Vec<i64> = self.observances.iter().map(|o| o.offset_to).collect();
+        offsets.sort_unstable_by(|a, b| b.cmp(a));
+        offsets.dedup();
+
+        for offset in &offsets {
+            let utc = local_ts - offset;
+            if self.offset_at(utc, until_year) == *offset {
+                return utc;
+            }
+        }
+
+        // Nonexistent local time (spring-forward gap): shift by the offset before the gap
+        local_ts - self.offset_at(local_ts - offsets[0], until_year)
+    }
+
+    // UTC offset (seconds) in effect at the given UTC instant
+    fn offset_at(&self, utc: i64, until_year: i32) -> i64 {
+        let mut transitions = self.transitions.lock().unwrap_or_else(|e| e.into_inner());
+        if transitions.until_year < until_year {
+            *transitions = self.expand_transitions(until_year + TRANSITION_CACHE_YEARS);
+        }
+
+        let index = transitions.onsets.partition_point(|(onset, _)| *onset <= utc);
+        match index.checked_sub(1) {
+            Some(latest) => transitions.onsets[latest].1,
+            None => {
+                // Before the first onset: use the offset the earliest observance transitions from
+                self.observances
+                    .iter()
+                    .min_by_key(|o| o.start)
+                    .map(|o| o.offset_from)
+                    .unwrap_or(0)
+            }
+        }
+    }
+
+    fn expand_transitions(&self, until_year: i32) -> Transitions {
+        let range_end = NaiveDate::from_ymd_opt(until_year, 12, 31)
+            .and_then(|d| d.and_hms_opt(23, 59, 59))
+            .map(|d| d.and_utc().timestamp())
+            .unwrap_or(i64::MAX);
+
+        let mut onsets = Vec::new();
+        for observance in &self.observances {
+            let mut starts = match observance.rrule {
+                Some(ref rule) => rule.expand(observance.start, |dt| dt.and_utc().timestamp(), range_end),
+                None => vec![observance.start],
+            };
+            starts.extend(observance.rdates.iter().copied());
+
+            // Onsets are written in the wall-clock time that was in effect before the transition
+            onsets.extend(
+                starts
+                    .into_iter()
+                    .map(|start| (start.and_utc().timestamp() - observance.offset_from, observance.offset_to)),
+            );
+        }
+        onsets.sort_by_key(|(onset, _)| *onset);
+
+        Transitions { until_year, onsets }
+    }
+}
+
+/// Resolves TZID parameters against IANA names, Windows names and the VTIMEZONE
+/// definitions embedded in a calendar.
+pub struct TimeZoneResolver {
+    custom: HashMap<String, Arc<CustomZone>>,
+}
+
+impl TimeZoneResolver {
+    pub fn from_calendar(timezones: &[IcalTimeZone]) -> Self {
+        let mut custom = HashMap::new();
+
+        for timezone in timezones {
+            let tzid = timezone
+                .properties
+                .iter()
+                .find(|p| p.name == "TZID")
+                .and_then(|p| p.value.clone());
+
+            if let (Some(tzid), Some(zone)) = (tzid, CustomZone::from_ical(timezone)) {
+                custom.insert(tzid, Arc::new(zone));
+            }
+        }
+
+        Self { custom }
+    }
+
+    pub fn resolve(&self, tzid: &str) -> Option<ResolvedZone> {
+        let tzid = tzid.trim().trim_matches('"');
+
+        if let Some(tz) = lookup_iana(tzid) {
+            return Some(ResolvedZone::Iana(tz));
+        }
+        if let Some(zone) = self.custom.get(tzid) {
+            return Some(ResolvedZone::Custom(zone.clone()));
+        }
+
+        log::warn!("Unknown TZID '{}', falling back to the user's timezone", tzid);
+        None
+    }
+}
+
+/// The operating system's IANA zone, or UTC if it cannot be determined.
+pub fn system_timezone() -> Tz {
+    iana_time_zone::get_timezone()
+        .ok()
+        .and_then(|name| name.parse().ok())
+        .unwrap_or(Tz::UTC)
+}
+
+/// UTC timestamp of the first instant of `date` in `tz`. Zones that skip midnight on a
+/// DST change (e.g. America/Santiago) start the day at the first valid local time.
+pub fn start_of_day(tz: Tz, date: NaiveDate) -> i64 {
+    (0..24)
+        .find_map(|hour| {
+            let time = NaiveTime::from_hms_opt(hour, 0, 0)?;
+            tz.from_local_datetime(&date.and_time(time)).earliest()
+        })
+        .map(|dt| dt.timestamp())
+        .unwrap_or_else(|| date.and_time(NaiveTime::MIN).and_utc().timestamp())
+}
+
+fn lookup_iana(tzid: &str) -> Option<Tz> {
+    if let Ok(tz) = tzid.parse::<Tz>() {
+        return Some(tz);
+    }
+
+    if let Some((_, iana)) = WINDOWS_ZONES.iter().find(|(name, _)| name.eq_ignore_ascii_case(tzid)) {
+        return iana.parse().ok();
+    }
+
+    // Prefixed IDs such as "/mozilla.org/20050126_1/America/New_York" or
+    // "/freeassociation.sourceforge.net/Tzfile/Europe/Berlin"
+    let segments: Vec<&str> = tzid.split('/').filter(|s| !s.is_empty()).collect();
+    (1..segments.len()).find_map(|skip| segments[skip..].join("/").parse().ok())
+}
+
+fn parse_local(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value.trim().trim_end_matches('Z'), "%Y%m%dT%H%M%S").ok()
+}
+
+// Parses a UTC offset such as +0100, -0530 or +013045 into seconds
+fn parse_offset(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (sign, digits) = match value.chars().next()? {
+        '+' => (1, &value[1..]),
+        '-' => (-1, &value[1..]),
+        _ => (1, value),
+    };
+    if digits.len() < 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let hours: i64 = digits[0..2].parse().ok()?;
+    let minutes: i64 = digits[2..4].parse().ok()?;
+    let seconds: i64 = digits.get(4..6).and_then(|s| s.parse().ok()).unwrap_or(0);
+    Some(sign * (hours * 3600 + minutes * 60 + seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+    use ical::IcalParser;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> i64 {
+        at(year, month, day, hour, minute).and_utc().timestamp()
+    }
+
+    // W. Europe rules the way Outlook exports them, under a TZID no lookup knows
+    const OUTLOOK_VTIMEZONE: &str = "BEGIN:VCALENDAR\r
+BEGIN:VTIMEZONE\r
+TZID:Custom Amsterdam\r
+BEGIN:STANDARD\r
+DTSTART:16010101T030000\r
+TZOFFSETFROM:+0200\r
+TZOFFSETTO:+0100\r
+RRULE:FREQ=YEARLY;INTERVAL=1;BYDAY=-1SU;BYMONTH=10\r
+END:STANDARD\r
+BEGIN:DAYLIGHT\r
+DTSTART:16010101T020000\r
+TZOFFSETFROM:+0100\r
+TZOFFSETTO:+0200\r
+RRULE:FREQ=YEARLY;INTERVAL=1;BYDAY=-1SU;BYMONTH=3\r
+END:DAYLIGHT\r
+END:VTIMEZONE\r
+END:VCALENDAR\r
+";
+
+    fn outlook_resolver() -> TimeZoneResolver {
+        let calendar = IcalParser::new(BufReader::new(OUTLOOK_VTIMEZONE.as_bytes()))
+            .next()
+            .unwrap()
+            .unwrap();
+        TimeZoneResolver::from_calendar(&calendar.timezones)
+    }
+
+    #[test]
+    fn resolves_windows_names() {
+        let resolver = TimeZoneResolver::from_calendar(&[]);
+        let zone = resolver.resolve("W. Europe Standard Time").unwrap();
+        assert!(matches!(zone, ResolvedZone::Iana(chrono_tz::Europe::Berlin)));
+
+        let zone = resolver.resolve("\"eastern standard time\"").unwrap();
+        assert!(matches!(zone, ResolvedZone::Iana(chrono_tz::America::New_York)));
+
+        // 2026-07-01 12:00 in Berlin is 10:00 UTC
+        let zone = resolver.resolve("W. Europe Standard Time").unwrap();
+        assert_eq!(zone.to_timestamp(at(2026, 7, 1, 12, 0)), utc(2026, 7, 1, 10, 0));
+    }
+
+    #[test]
+    fn resolves_prefixed_iana_names() {
+        let resolver = TimeZoneResolver::from_calendar(&[]);
+        let zone = resolver.resolve("/mozilla.org/20050126_1/America/New_York").unwrap();
+        assert!(matches!(zone, ResolvedZone::Iana(chrono_tz::America::New_York)));
+        assert!(resolver.resolve("Nowhere Standard Time").is_none());
+    }
+
+    #[test]
+    fn evaluates_embedded_vtimezone() {
+        let zone = outlook_resolver().resolve("Custom Amsterdam").unwrap();
+        assert!(matches!(zone, ResolvedZone::Custom(_)));
+
+        assert_eq!(zone.to_timestamp(at(2026, 1, 15, 9, 0)), utc(2026, 1, 15, 8, 0));
+        assert_eq!(zone.to_timestamp(at(2026, 7, 15, 9, 0)), utc(2026, 7, 15, 7, 0));
+        // Summer time starts 2026-03-29 and ends 2026-10-25
+        assert_eq!(zone.to_timestamp(at(2026, 3, 29, 1, 59)), utc(2026, 3, 29, 0, 59));
+        assert_eq!(zone.to_timestamp(at(2026, 3, 29, 3, 0)), utc(2026, 3, 29, 1, 0));
+        assert_eq!(zone.to_timestamp(at(2026, 10, 25, 3, 30)), utc(2026, 10, 25, 2, 30));
+    }
+
+    #[test]
+    fn embedded_vtimezone_edge_cases() {
+        let zone = outlook_resolver().resolve("Custom Amsterdam").unwrap();
+
+        // 02:30 on the fall-back night happens twice; the earlier instant wins
+        assert_eq!(zone.to_timestamp(at(2026, 10, 25, 2, 30)), utc(2026, 10, 25, 0, 30));
+        // 02:30 on the spring-forward night doesn't exist and is shifted by the winter offset
+        assert_eq!(zone.to_timestamp(at(2026, 3, 29, 2, 30)), utc(2026, 3, 29, 1, 30));
+    }
+
+    #[test]
+    fn cached_transitions_cover_later_and_earlier_years() {
+        let zone = outlook_resolver().resolve("Custom Amsterdam").unwrap();
+
+        assert_eq!(zone.to_timestamp(at(2026, 7, 15, 9, 0)), utc(2026, 7, 15, 7, 0));
+        // Beyond the first expansion, then back before it
+        assert_eq!(zone.to_timestamp(at(2060, 7, 15, 9, 0)), utc(2060, 7, 15, 7, 0));
+        assert_eq!(zone.to_timestamp(at(1990, 1, 15, 9, 0)), utc(1990, 1, 15, 8, 0));
+        assert_eq!(zone.to_timestamp(at(2026, 1, 15, 9, 0)), utc(2026, 1, 15, 8, 0));
+    }
+}
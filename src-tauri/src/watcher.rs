@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::mpsc::{self, UnboundedSender};
+use crate::error::{AppError, Result};
+use crate::models::*;
+use crate::database;
+use crate::commands::refresh_calendar_source;
+
+// Sync tools and editors write a file in several steps; wait for them to settle
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+pub struct CalendarWatcher {
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    sender: UnboundedSender<PathBuf>,
+}
+
+fn get_calendar_config(app: &AppHandle) -> Result<CalendarConfig> {
+    Ok(database::get_setting(&database::open(app)?, "calendar_config")?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+/// Starts watching the files of enabled `ics_file` and `vdir` sources. Changed sources are re-parsed
+/// into the cache and announced to the window with a "calendar-updated" event.
+pub fn start(app: AppHandle) {
+    let (sender, mut receiver) = mpsc::unbounded_channel::<PathBuf>();
+    app.manage(CalendarWatcher {
+        watcher: Mutex::new(None),
+        sender,
+    });
+
+    if let Err(e) = sync(&app) {
+        log::warn!("Failed to watch calendar files: {}", e);
+    }
+
+    tauri::async_runtime::spawn(async move {
+        while let Some(path) = receiver.recv().await {
+            let mut changed = HashSet::from([normalize(&path)]);
+            while let Ok(Some(path)) = tokio::time::timeout(DEBOUNCE, receiver.recv()).await {
+                changed.insert(normalize(&path));
+            }
+
+            if let Err(e) = refresh_changed(&app, &changed).await {
+                log::warn!("Failed to refresh changed calendar files: {}", e);
+            }
+        }
+    });
+}
+
+/// Replaces the watch list with the current configuration; call after the sources change.
+pub fn sync(app: &AppHandle) -> Result<()> {
+    let state = app.state::<CalendarWatcher>();
+    let config = get_calendar_config(app)?;
+
+    // Single files are watched through their parent directory, since tools that save
+    // atomically replace the file itself; vdir trees are watched as a whole
+    let targets: HashSet<(PathBuf, bool)> = local_sources(&config)
+        .filter_map(|local| match local.kind {
+            LocalKind::File => local.path.parent().map(|dir| (dir.to_path_buf(), false)),
+            LocalKind::Directory => Some((local.path, true)),
+        })
+        .collect();
+
+    let mut guard = state.watcher.lock().map_err(|e| AppError::Other(e.to_string()))?;
+    *guard = None;
+    if targets.is_empty() {
+        return Ok(());
+    }
+
+    let sender = state.sender.clone();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        if let Ok(event) = result {
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+                for path in event.paths {
+                    let _ = sender.send(path);
+                }
+            }
+        }
+    })
+    .map_err(|e| AppError::Other(format!("Failed to create file watcher: {}", e)))?;
+
+    for (directory, recursive) in &targets {
+        let mode = if *recursive { RecursiveMode::Recursive } else { RecursiveMode::NonRecursive };
+        if let Err(e) = watcher.watch(directory, mode) {
+            log::warn!("Failed to watch {}: {}", directory.display(), e);
+        }
+    }
+
+    *guard = Some(watcher);
+    Ok(())
+}
+
+async fn refresh_changed(app: &AppHandle, changed: &HashSet<PathBuf>) -> Result<()> {
+    let config = get_calendar_config(app)?;
+    let mut updated = Vec::new();
+
+    for LocalSource { source, path, kind } in local_sources(&config) {
+        let affected = match kind {
+            LocalKind::File => changed.contains(&path),
+            LocalKind::Directory => changed.iter().any(|p| p.starts_with(&path)),
+        };
+        if !affected {
+            continue;
+        }
+        match refresh_calendar_source(app, source).await {
+            Ok(()) => updated.push(source.id.clone()),
+            Err(e) => log::warn!("Failed to re-read calendar '{}': {}", source.name, e),
+        }
+    }
+
+    if !updated.is_empty() {
+        app.emit("calendar-updated", updated)
+            .map_err(|e| AppError::Other(e.to_string()))?;
+    }
+    Ok(())
+}
+
+enum LocalKind {
+    File,
+    Directory,
+}
+
+struct LocalSource<'a> {
+    source: &'a CalendarSource,
+    path: PathBuf,
+    kind: LocalKind,
+}
+
+fn local_sources(config: &CalendarConfig) -> impl Iterator<Item = LocalSource<'_>> {
+    config.enabled_sources().filter_map(|source| {
+        let path = Path::new(source.ics_path.as_deref()?);
+        let (path, kind) = match source.source_type.as_str() {
+            "ics_file" => (normalize(path), LocalKind::File),
+            // Resolved fully, so that paths reported for files inside it share the prefix
+            "vdir" => (path.canonicalize().unwrap_or_else(|_| normalize(path)), LocalKind::Directory),
+            _ => return None,
+        };
+        Some(LocalSource { source, path, kind })
+    })
+}
+
+// Canonical directory plus file name, so configured paths and watcher paths compare equal
+// even when the file itself is momentarily missing
+fn normalize(path: &Path) -> PathBuf {
+    match (path.parent().and_then(|p| p.canonicalize().ok()), path.file_name()) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => path.to_path_buf(),
+    }
+}
@@ -0,0 +1,113 @@
+import { useEffect, useState } from "react";
+import { Button } from "../ui";
+import { useSettingsStore } from "../../store/settingsStore";
+import type { GitHubAccount, GitHubDeviceCode } from "../../types";
+
+interface GitHubSignInProps {
+  name: string;
+  apiUrl: string | null;
+  graphqlUrl: string | null;
+  clientId: string | null;
+  label?: string;
+  onSignedIn: (account: GitHubAccount) => void;
+}
+
+// Device-flow sign-in: shows the code to enter on GitHub and waits until the user has
+export function GitHubSignIn({
+  name,
+  apiUrl,
+  graphqlUrl,
+  clientId,
+  label = "Sign in with GitHub",
+  onSignedIn,
+}: GitHubSignInProps) {
+  const { startGitHubDeviceFlow, pollGitHubDeviceFlow } = useSettingsStore();
+  const [deviceCode, setDeviceCode] = useState<GitHubDeviceCode | null>(null);
+  const [interval, setPollInterval] = useState(5);
+  const [polls, setPolls] = useState(0);
+  const [starting, setStarting] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const start = async () => {
+    setStarting(true);
+    setError(null);
+    try {
+      const code = await startGitHubDeviceFlow(apiUrl, graphqlUrl, clientId);
+      setPollInterval(code.interval);
+      setDeviceCode(code);
+      window.open(code.verification_uri, "_blank");
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setStarting(false);
+    }
+  };
+
+  useEffect(() => {
+    if (!deviceCode) return;
+
+    let cancelled = false;
+    const timer = setTimeout(async () => {
+      try {
+        const result = await pollGitHubDeviceFlow(
+          name,
+          deviceCode.device_code,
+          apiUrl,
+          graphqlUrl,
+          clientId,
+        );
+        if (cancelled) return;
+        if (result.account) {
+          setDeviceCode(null);
+          onSignedIn(result.account);
+        } else {
+          if (result.interval) setPollInterval(result.interval);
+          setPolls((n) => n + 1);
+        }
+      } catch (err) {
+        if (cancelled) return;
+        setDeviceCode(null);
+        setError(String(err));
+      }
+    }, interval * 1000);
+
+    return () => {
+      cancelled = true;
+      clearTimeout(timer);
+    };
+  }, [deviceCode, interval, polls]);
+
+  if (!deviceCode) {
+    return (
+      <div className="space-y-2">
+        <Button size="sm" variant="secondary" onClick={start} loading={starting}>
+          {label}
+        </Button>
+        {error && <p className="text-xs text-red-400">{error}</p>}
+      </div>
+    );
+  }
+
+  return (
+    <div className="p-3 bg-gray-900 border border-gray-800 rounded-lg space-y-2">
+      <p className="text-sm text-gray-400">
+        Enter this code at{" "}
+        <a
+          href={deviceCode.verification_uri}
+          target="_blank"
+          rel="noopener noreferrer"
+          className="text-brand-400 hover:text-brand-300"
+        >
+          {deviceCode.verification_uri}
+        </a>
+      </p>
+      <p className="text-2xl font-mono tracking-widest text-white">{deviceCode.user_code}</p>
+      <div className="flex items-center gap-3">
+        <span className="text-xs text-gray-500">Waiting for GitHub...</span>
+        <Button size="sm" variant="ghost" onClick={() => setDeviceCode(null)}>
+          Cancel
+        </Button>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1 @@
+export { GitHubSignIn } from "./GitHubSignIn";
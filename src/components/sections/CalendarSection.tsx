@@ -1,15 +1,32 @@
-import { Section, Card, Badge, EmptyState } from "../ui";
-import type { CalendarEvent } from "../../types";
+import type { MouseEvent } from "react";
+import { Section, Card, Badge, Button, EmptyState } from "../ui";
+import type {
+  CalendarConflict,
+  CalendarEvent,
+  ConferenceProvider,
+} from "../../types";
 import { format } from "date-fns";
 
+const PROVIDER_LABELS: Record<ConferenceProvider, string> = {
+  zoom: "Zoom",
+  google_meet: "Meet",
+  teams: "Teams",
+  jitsi: "Jitsi",
+  webex: "Webex",
+};
+
 interface CalendarSectionProps {
   events: CalendarEvent[];
+  invitations: CalendarEvent[];
+  conflicts: CalendarConflict[];
   loading: boolean;
   configured: boolean;
 }
 
 export function CalendarSection({
   events,
+  invitations,
+  conflicts,
   loading,
   configured,
 }: CalendarSectionProps) {
@@ -24,6 +41,8 @@ export function CalendarSection({
     );
   }
 
+  const conflictedIds = new Set(conflicts.flatMap((c) => c.event_ids));
+
   // Sort events: all-day first, then by start time
   const sortedEvents = [...events].sort((a, b) => {
     if (a.all_day && !b.all_day) return -1;
@@ -45,8 +64,30 @@ export function CalendarSection({
         />
       ) : (
         <div className="space-y-2">
+          {invitations.length > 0 && (
+            <p className="text-xs text-yellow-400">
+              {invitations.length} invitation
+              {invitations.length === 1 ? "" : "s"} awaiting your response
+            </p>
+          )}
+          {conflicts.map((conflict) => (
+            <p
+              key={conflict.event_ids.join("|")}
+              className="text-xs text-red-400"
+            >
+              {conflict.kind === "out_of_office"
+                ? "Scheduled while out of office"
+                : "Double-booked"}{" "}
+              at {format(new Date(conflict.start * 1000), "h:mm a")}:{" "}
+              {conflict.summaries.join(" / ")}
+            </p>
+          ))}
           {sortedEvents.map((event) => (
-            <EventCard key={event.id} event={event} />
+            <EventCard
+              key={event.id}
+              event={event}
+              conflicted={conflictedIds.has(event.id)}
+            />
           ))}
         </div>
       )}
@@ -54,7 +95,13 @@ export function CalendarSection({
   );
 }
 
-function EventCard({ event }: { event: CalendarEvent }) {
+function EventCard({
+  event,
+  conflicted,
+}: {
+  event: CalendarEvent;
+  conflicted: boolean;
+}) {
   const startDate = new Date(event.start_time * 1000);
   const endDate = new Date(event.end_time * 1000);
 
@@ -64,20 +111,40 @@ function EventCard({ event }: { event: CalendarEvent }) {
     }
   };
 
+  const joinMeeting = (e: MouseEvent) => {
+    e.stopPropagation();
+    if (event.join_url) {
+      window.open(event.join_url, "_blank");
+    }
+  };
+
   const formatTime = (date: Date) => format(date, "h:mm a");
+  const declined = event.my_status === "declined";
 
   return (
     <Card
       hover={!!event.html_link}
       onClick={event.html_link ? openUrl : undefined}
-      className={`p-3 ${event.is_now ? "border-brand-500 bg-brand-950/30" : ""}`}
+      className={`p-3 ${event.is_now ? "border-brand-500 bg-brand-950/30" : ""} ${declined ? "opacity-50" : ""}`}
     >
       <div className="flex items-start justify-between gap-3">
         <div className="flex-1 min-w-0">
           <div className="flex items-center gap-2">
-            <p className="text-sm font-medium text-gray-200 truncate">
+            {event.color && (
+              <span
+                className="w-2 h-2 rounded-full flex-shrink-0"
+                style={{ backgroundColor: event.color }}
+              />
+            )}
+            <p
+              className={`text-sm font-medium text-gray-200 truncate ${declined ? "line-through" : ""}`}
+            >
               {event.summary}
             </p>
+            {conflicted && <Badge variant="error">Conflict</Badge>}
+            {event.my_status === "needs_action" && (
+              <Badge variant="info">Needs response</Badge>
+            )}
             {event.is_now && <Badge variant="success">Now</Badge>}
             {event.is_soon && !event.is_now && (
               <Badge variant="warning">Soon</Badge>
@@ -91,6 +158,14 @@ function EventCard({ event }: { event: CalendarEvent }) {
           )}
         </div>
 
+        {event.join_url && (
+          <Button size="sm" variant="secondary" onClick={joinMeeting}>
+            Join
+            {event.conference_provider &&
+              ` ${PROVIDER_LABELS[event.conference_provider]}`}
+          </Button>
+        )}
+
         <div className="text-right flex-shrink-0">
           {event.all_day ? (
             <span className="text-xs text-gray-400">All day</span>
@@ -1,4 +1,7 @@
-import { Section, Card, Badge, EmptyState } from "../ui";
+import { useState, type MouseEvent } from "react";
+import { format } from "date-fns";
+import { Section, Card, Badge, Button, EmptyState } from "../ui";
+import { useBriefStore } from "../../store/briefStore";
 import type { GitHubBriefData, GitHubPullRequest, GitHubIssue, GitHubNotification } from "../../types";
 
 interface GitHubSectionProps {
@@ -8,6 +11,9 @@ interface GitHubSectionProps {
 }
 
 export function GitHubSection({ data, loading, configured }: GitHubSectionProps) {
+  // Account the lists are narrowed to; null shows every account
+  const [accountId, setAccountId] = useState<string | null>(null);
+
   if (!configured) {
     return (
       <Section title="GitHub" icon={<GitHubIcon />}>
@@ -19,11 +25,26 @@ export function GitHubSection({ data, loading, configured }: GitHubSectionProps)
     );
   }
 
+  const accounts = data?.accounts ?? [];
+  const selected = accounts.find((a) => a.id === accountId) ?? null;
+  const inAccount = <T extends { account: string }>(items: T[]) =>
+    selected ? items.filter((item) => item.account === selected.id) : items;
+  const accountNames = new Map(accounts.map((a) => [a.id, a.name]));
+  // Cards only need to say where they came from when several accounts are mixed together
+  const accountName = (id: string) =>
+    accounts.length > 1 && !selected ? accountNames.get(id) : undefined;
+
+  const prsToReview = inAccount(data?.prs_to_review ?? []);
+  const myOpenPrs = inAccount(data?.my_open_prs ?? []);
+  const mentionedIssues = inAccount(data?.mentioned_issues ?? []);
+  const unreadNotifications = inAccount(data?.notifications ?? []).filter(n => n.unread);
+  const totals = selected?.totals ?? data?.totals;
+
   const totalItems =
-    (data?.prs_to_review.length ?? 0) +
-    (data?.my_open_prs.length ?? 0) +
-    (data?.mentioned_issues.length ?? 0) +
-    (data?.notifications.filter(n => n.unread).length ?? 0);
+    prsToReview.length +
+    myOpenPrs.length +
+    mentionedIssues.length +
+    unreadNotifications.length;
 
   return (
     <Section
@@ -36,60 +57,97 @@ export function GitHubSection({ data, loading, configured }: GitHubSectionProps)
         <EmptyState title="No data yet" description="Click refresh to fetch your GitHub data." />
       ) : (
         <div className="space-y-4">
+          {data?.rate_limited_until && (
+            <p className="text-xs text-yellow-400">
+              Rate limited until {format(new Date(data.rate_limited_until * 1000), "HH:mm")} — showing cached data
+            </p>
+          )}
+
+          {accounts.length > 1 && (
+            <div className="flex flex-wrap gap-1.5">
+              <Button
+                size="sm"
+                variant={selected ? "ghost" : "secondary"}
+                onClick={() => setAccountId(null)}
+              >
+                All
+              </Button>
+              {accounts.map((account) => (
+                <Button
+                  key={account.id}
+                  size="sm"
+                  variant={selected?.id === account.id ? "secondary" : "ghost"}
+                  onClick={() => setAccountId(account.id)}
+                >
+                  {account.name}
+                </Button>
+              ))}
+            </div>
+          )}
+
           {/* PRs to Review */}
-          {data?.prs_to_review && data.prs_to_review.length > 0 && (
+          {prsToReview.length > 0 && (
             <div>
-              <h3 className="text-xs font-medium text-gray-500 mb-2">PRs to Review</h3>
+              <h3 className="text-xs font-medium text-gray-500 mb-2">
+                PRs to Review
+                <ListCount shown={prsToReview.length} total={totals?.prs_to_review ?? 0} />
+              </h3>
               <div className="space-y-2">
-                {data.prs_to_review.map((pr) => (
-                  <PRCard key={pr.id} pr={pr} type="review" />
+                {prsToReview.map((pr) => (
+                  <PRCard key={`${pr.account}-${pr.id}`} pr={pr} type="review" accountName={accountName(pr.account)} />
                 ))}
               </div>
             </div>
           )}
 
           {/* My Open PRs */}
-          {data?.my_open_prs && data.my_open_prs.length > 0 && (
+          {myOpenPrs.length > 0 && (
             <div>
-              <h3 className="text-xs font-medium text-gray-500 mb-2">Your Open PRs</h3>
+              <h3 className="text-xs font-medium text-gray-500 mb-2">
+                Your Open PRs
+                <ListCount shown={myOpenPrs.length} total={totals?.my_open_prs ?? 0} />
+              </h3>
               <div className="space-y-2">
-                {data.my_open_prs.map((pr) => (
-                  <PRCard key={pr.id} pr={pr} type="mine" />
+                {myOpenPrs.map((pr) => (
+                  <PRCard key={`${pr.account}-${pr.id}`} pr={pr} type="mine" accountName={accountName(pr.account)} />
                 ))}
               </div>
             </div>
           )}
 
           {/* Mentioned Issues */}
-          {data?.mentioned_issues && data.mentioned_issues.length > 0 && (
+          {mentionedIssues.length > 0 && (
             <div>
-              <h3 className="text-xs font-medium text-gray-500 mb-2">Mentioned In</h3>
+              <h3 className="text-xs font-medium text-gray-500 mb-2">
+                Mentioned In
+                <ListCount shown={mentionedIssues.length} total={totals?.mentioned_issues ?? 0} />
+              </h3>
               <div className="space-y-2">
-                {data.mentioned_issues.map((issue) => (
-                  <IssueCard key={issue.id} issue={issue} />
+                {mentionedIssues.map((issue) => (
+                  <IssueCard key={`${issue.account}-${issue.id}`} issue={issue} accountName={accountName(issue.account)} />
                 ))}
               </div>
             </div>
           )}
 
           {/* Notifications */}
-          {data?.notifications && data.notifications.filter(n => n.unread).length > 0 && (
+          {unreadNotifications.length > 0 && (
             <div>
               <h3 className="text-xs font-medium text-gray-500 mb-2">Notifications</h3>
               <div className="space-y-2">
-                {data.notifications.filter(n => n.unread).slice(0, 5).map((notif) => (
-                  <NotificationCard key={notif.id} notification={notif} />
+                {unreadNotifications.slice(0, 5).map((notif) => (
+                  <NotificationCard
+                    key={`${notif.account}-${notif.id}`}
+                    notification={notif}
+                    accountName={accountName(notif.account)}
+                  />
                 ))}
               </div>
             </div>
           )}
 
           {/* Empty state when all arrays are empty */}
-          {data &&
-           data.prs_to_review.length === 0 &&
-           data.my_open_prs.length === 0 &&
-           data.mentioned_issues.length === 0 &&
-           data.notifications.filter(n => n.unread).length === 0 && (
+          {data && totalItems === 0 && (
             <EmptyState
               title="All clear!"
               description="No PRs to review, no open PRs, and no new notifications."
@@ -101,7 +159,15 @@ export function GitHubSection({ data, loading, configured }: GitHubSectionProps)
   );
 }
 
-function PRCard({ pr, type }: { pr: GitHubPullRequest; type: "review" | "mine" }) {
+function PRCard({
+  pr,
+  type,
+  accountName,
+}: {
+  pr: GitHubPullRequest;
+  type: "review" | "mine";
+  accountName?: string;
+}) {
   const openUrl = () => {
     window.open(pr.html_url, "_blank");
   };
@@ -116,20 +182,29 @@ function PRCard({ pr, type }: { pr: GitHubPullRequest; type: "review" | "mine" }
           </p>
         </div>
         <div className="flex items-center gap-1.5 flex-shrink-0">
+          {accountName && <Badge variant="default">{accountName}</Badge>}
           {pr.draft && <Badge variant="default">Draft</Badge>}
+          {pr.ci_status === "failure" && <Badge variant="error">CI failing</Badge>}
+          {pr.ci_status === "pending" && <Badge variant="info">CI running</Badge>}
+          {type === "mine" && pr.mergeable === "conflicting" && (
+            <Badge variant="error">Conflicts</Badge>
+          )}
           {type === "mine" && pr.review_status === "approved" && (
             <Badge variant="success">Approved</Badge>
           )}
           {type === "mine" && pr.review_status === "changes_requested" && (
             <Badge variant="warning">Changes</Badge>
           )}
+          {type === "mine" && !pr.draft && pr.review_status === "pending" && (
+            <Badge variant="default">Awaiting review</Badge>
+          )}
         </div>
       </div>
     </Card>
   );
 }
 
-function IssueCard({ issue }: { issue: GitHubIssue }) {
+function IssueCard({ issue, accountName }: { issue: GitHubIssue; accountName?: string }) {
   const openUrl = () => {
     window.open(issue.html_url, "_blank");
   };
@@ -139,18 +214,38 @@ function IssueCard({ issue }: { issue: GitHubIssue }) {
       <p className="text-sm font-medium text-gray-200 truncate">{issue.title}</p>
       <p className="text-xs text-gray-500 mt-0.5">
         {issue.repo_full_name} #{issue.number}
+        {accountName && <span className="text-gray-600"> · {accountName}</span>}
       </p>
     </Card>
   );
 }
 
-function NotificationCard({ notification }: { notification: GitHubNotification }) {
+function NotificationCard({
+  notification,
+  accountName,
+}: {
+  notification: GitHubNotification;
+  accountName?: string;
+}) {
+  const {
+    markNotificationRead,
+    markNotificationDone,
+    markRepoNotificationsRead,
+    unsubscribeNotification,
+  } = useBriefStore();
+
   const openUrl = () => {
     if (notification.url) {
       window.open(notification.url, "_blank");
     }
   };
 
+  // Triage without opening the thread
+  const action = (run: () => Promise<void>) => (e: MouseEvent) => {
+    e.stopPropagation();
+    run();
+  };
+
   const reasonLabels: Record<string, string> = {
     review_requested: "Review requested",
     mention: "Mentioned",
@@ -165,14 +260,47 @@ function NotificationCard({ notification }: { notification: GitHubNotification }
       <div className="flex items-start justify-between gap-2">
         <div className="flex-1 min-w-0">
           <p className="text-sm font-medium text-gray-200 truncate">{notification.subject_title}</p>
-          <p className="text-xs text-gray-500 mt-0.5">{notification.repo_full_name}</p>
+          <p className="text-xs text-gray-500 mt-0.5">
+            {notification.repo_full_name}
+            {accountName && <span className="text-gray-600"> · {accountName}</span>}
+          </p>
         </div>
         <Badge variant="info">{reasonLabels[notification.reason] ?? notification.reason}</Badge>
       </div>
+      <div className="flex flex-wrap gap-1.5 mt-2">
+        <Button size="sm" variant="secondary" onClick={action(() => markNotificationRead(notification))}>
+          Read
+        </Button>
+        <Button size="sm" variant="secondary" onClick={action(() => markNotificationDone(notification))}>
+          Done
+        </Button>
+        <Button
+          size="sm"
+          variant="secondary"
+          onClick={action(() => markRepoNotificationsRead(notification))}
+        >
+          Read all in repo
+        </Button>
+        <Button size="sm" variant="secondary" onClick={action(() => unsubscribeNotification(notification))}>
+          Unsubscribe
+        </Button>
+      </div>
     </Card>
   );
 }
 
+// "20 of 57" when the list was capped, nothing otherwise
+function ListCount({ shown, total }: { shown: number; total: number }) {
+  if (total <= shown) {
+    return null;
+  }
+  return (
+    <span className="ml-1 text-gray-600">
+      ({shown} of {total})
+    </span>
+  );
+}
+
 function GitHubIcon() {
   return (
     <svg className="w-4 h-4" viewBox="0 0 24 24" fill="currentColor">
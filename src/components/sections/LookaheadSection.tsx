@@ -0,0 +1,79 @@
+import { Section, Card } from "../ui";
+import type { Lookahead } from "../../types";
+import { TaskCard } from "./TasksSection";
+import { format } from "date-fns";
+
+interface LookaheadSectionProps {
+  lookahead: Lookahead;
+  loading: boolean;
+}
+
+export function LookaheadSection({ lookahead, loading }: LookaheadSectionProps) {
+  const { first_meeting, meeting_count, deadlines } = lookahead;
+  const day = format(new Date(lookahead.next_day_start * 1000), "EEEE");
+
+  return (
+    <Section title="Coming Up" icon={<LookaheadIcon />} loading={loading}>
+      <div className="space-y-2">
+        <Card className="p-3">
+          {first_meeting ? (
+            <div className="flex items-start justify-between gap-3">
+              <div className="flex-1 min-w-0">
+                <p className="text-xs text-gray-500">First meeting {day}</p>
+                <div className="flex items-center gap-2 mt-0.5">
+                  {first_meeting.color && (
+                    <span
+                      className="w-2 h-2 rounded-full flex-shrink-0"
+                      style={{ backgroundColor: first_meeting.color }}
+                    />
+                  )}
+                  <p className="text-sm font-medium text-gray-200 truncate">
+                    {first_meeting.summary}
+                  </p>
+                </div>
+                {meeting_count > 1 && (
+                  <p className="text-xs text-gray-500 mt-0.5">
+                    {meeting_count - 1} more meeting
+                    {meeting_count === 2 ? "" : "s"} after it
+                  </p>
+                )}
+              </div>
+              <span className="text-xs text-gray-400 flex-shrink-0">
+                {format(new Date(first_meeting.start_time * 1000), "HH:mm")}
+              </span>
+            </div>
+          ) : (
+            <p className="text-sm text-gray-400">No meetings {day}</p>
+          )}
+        </Card>
+
+        {deadlines.length > 0 && (
+          <>
+            <p className="text-xs text-gray-500 pt-1">Due this week</p>
+            {deadlines.map((task) => (
+              <TaskCard key={`${task.source}:${task.id}`} task={task} />
+            ))}
+          </>
+        )}
+      </div>
+    </Section>
+  );
+}
+
+function LookaheadIcon() {
+  return (
+    <svg
+      className="w-4 h-4"
+      fill="none"
+      viewBox="0 0 24 24"
+      stroke="currentColor"
+      strokeWidth={2}
+    >
+      <path
+        strokeLinecap="round"
+        strokeLinejoin="round"
+        d="M13 7l5 5m0 0l-5 5m5-5H6"
+      />
+    </svg>
+  );
+}
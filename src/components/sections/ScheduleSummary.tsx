@@ -0,0 +1,48 @@
+import { format } from "date-fns";
+import type { DaySchedule } from "../../types";
+
+interface ScheduleSummaryProps {
+  schedule: DaySchedule;
+}
+
+export function ScheduleSummary({ schedule }: ScheduleSummaryProps) {
+  const { next_free, longest_focus_block, back_to_back } = schedule;
+
+  if (schedule.meeting_count === 0) {
+    return (
+      <p className="text-sm text-gray-200 mt-1">
+        No meetings today — the whole day is yours.
+      </p>
+    );
+  }
+
+  return (
+    <div className="mt-1 space-y-0.5">
+      <p className="text-sm text-gray-200">
+        {next_free
+          ? `You have ${formatMinutes(next_free.duration_minutes)} free from ${formatTime(next_free.start)}`
+          : "No free time left in your working day"}
+      </p>
+      <p className="text-xs text-gray-500">
+        {schedule.meeting_count} meeting
+        {schedule.meeting_count === 1 ? "" : "s"} ·{" "}
+        {formatMinutes(schedule.meeting_minutes)} in meetings
+        {longest_focus_block &&
+          ` · longest focus block ${formatTime(longest_focus_block.start)}–${formatTime(longest_focus_block.end)}`}
+        {back_to_back.length > 0 &&
+          ` · ${back_to_back.length} back-to-back run${back_to_back.length === 1 ? "" : "s"}`}
+      </p>
+    </div>
+  );
+}
+
+function formatTime(timestamp: number) {
+  return format(new Date(timestamp * 1000), "HH:mm");
+}
+
+function formatMinutes(minutes: number) {
+  const hours = Math.floor(minutes / 60);
+  const rest = minutes % 60;
+  if (hours === 0) return `${rest}m`;
+  return rest === 0 ? `${hours}h` : `${hours}h${rest.toString().padStart(2, "0")}m`;
+}
@@ -0,0 +1,87 @@
+import { Section, Card, Badge } from "../ui";
+import type { CalendarTask } from "../../types";
+import { format } from "date-fns";
+
+interface TasksSectionProps {
+  tasks: CalendarTask[];
+  loading: boolean;
+}
+
+export function TasksSection({ tasks, loading }: TasksSectionProps) {
+  // Tasks only come from calendar feeds that export VTODOs, so stay out of the way otherwise
+  if (tasks.length === 0) {
+    return null;
+  }
+
+  return (
+    <Section
+      title="Tasks Due"
+      icon={<TaskIcon />}
+      badge={tasks.length}
+      loading={loading}
+    >
+      <div className="space-y-2">
+        {tasks.map((task) => (
+          <TaskCard key={`${task.source}:${task.id}`} task={task} />
+        ))}
+      </div>
+    </Section>
+  );
+}
+
+export function TaskCard({ task }: { task: CalendarTask }) {
+  const due = task.due_time ? new Date(task.due_time * 1000) : null;
+
+  return (
+    <Card className="p-3">
+      <div className="flex items-start justify-between gap-3">
+        <div className="flex-1 min-w-0">
+          <div className="flex items-center gap-2">
+            {task.color && (
+              <span
+                className="w-2 h-2 rounded-full flex-shrink-0"
+                style={{ backgroundColor: task.color }}
+              />
+            )}
+            <p className="text-sm font-medium text-gray-200 truncate">
+              {task.summary}
+            </p>
+            {task.is_overdue && <Badge variant="error">Overdue</Badge>}
+            {task.priority !== null && task.priority <= 4 && (
+              <Badge variant="warning">High priority</Badge>
+            )}
+          </div>
+          {task.percent_complete !== null && (
+            <p className="text-xs text-gray-500 mt-0.5">
+              {task.percent_complete}% complete
+            </p>
+          )}
+        </div>
+
+        {due && (
+          <span className="text-xs text-gray-400 flex-shrink-0">
+            {task.due_all_day ? format(due, "MMM d") : format(due, "MMM d, h:mm a")}
+          </span>
+        )}
+      </div>
+    </Card>
+  );
+}
+
+function TaskIcon() {
+  return (
+    <svg
+      className="w-4 h-4"
+      fill="none"
+      viewBox="0 0 24 24"
+      stroke="currentColor"
+      strokeWidth={2}
+    >
+      <path
+        strokeLinecap="round"
+        strokeLinejoin="round"
+        d="M9 5H7a2 2 0 00-2 2v12a2 2 0 002 2h10a2 2 0 002-2V7a2 2 0 00-2-2h-2M9 5a2 2 0 002 2h2a2 2 0 002-2M9 5a2 2 0 012-2h2a2 2 0 012 2m-6 9l2 2 4-4"
+      />
+    </svg>
+  );
+}
@@ -1,3 +1,6 @@
 export { GitHubSection } from "./GitHubSection";
 export { CalendarSection } from "./CalendarSection";
 export { EmailSection } from "./EmailSection";
+export { ScheduleSummary } from "./ScheduleSummary";
+export { TasksSection } from "./TasksSection";
+export { LookaheadSection } from "./LookaheadSection";
@@ -2,9 +2,14 @@ import { AppShell } from "../components/layout";
 import { GitHubSection } from "../components/sections/GitHubSection";
 import { CalendarSection } from "../components/sections/CalendarSection";
 import { EmailSection } from "../components/sections/EmailSection";
+import { ScheduleSummary } from "../components/sections/ScheduleSummary";
+import { TasksSection } from "../components/sections/TasksSection";
+import { LookaheadSection } from "../components/sections/LookaheadSection";
 import { useBriefStore } from "../store/briefStore";
 import { useSettingsStore } from "../store/settingsStore";
 import { useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
+import type { MeetingReminder } from "../types";
 
 export function DailyBrief() {
   const { brief, loading, refreshing, lastUpdated, loadBrief, refreshBrief } = useBriefStore();
@@ -14,6 +19,26 @@ export function DailyBrief() {
     loadBrief();
   }, [loadBrief]);
 
+  // Reload from cache so "Now"/"Soon" badges and the free-time summary stay current
+  useEffect(() => {
+    const unlisten = listen<MeetingReminder>("meeting-reminder", () => {
+      loadBrief();
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [loadBrief]);
+
+  // A watched calendar file changed on disk and has already been re-read into the cache
+  useEffect(() => {
+    const unlisten = listen<string[]>("calendar-updated", () => {
+      loadBrief();
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [loadBrief]);
+
   return (
     <AppShell
       onRefresh={refreshBrief}
@@ -25,6 +50,7 @@ export function DailyBrief() {
           <p className="text-gray-400 text-sm">
             WTF should I focus on today?
           </p>
+          {brief && <ScheduleSummary schedule={brief.schedule} />}
         </div>
 
         <GitHubSection
@@ -35,10 +61,18 @@ export function DailyBrief() {
 
         <CalendarSection
           events={brief?.calendar ?? []}
+          invitations={brief?.pending_invitations ?? []}
+          conflicts={brief?.conflicts ?? []}
           loading={loading}
-          configured={settings?.calendar_config.source_type !== "none"}
+          configured={(settings?.calendar_config.sources.length ?? 0) > 0}
         />
 
+        <TasksSection tasks={brief?.tasks ?? []} loading={loading} />
+
+        {brief && (settings?.calendar_config.sources.length ?? 0) > 0 && (
+          <LookaheadSection lookahead={brief.lookahead} loading={loading} />
+        )}
+
         <EmailSection
           emails={brief?.email ?? []}
           loading={loading}
@@ -13,9 +13,14 @@ interface CalendarStepProps {
 type SourceType = "ics_url" | "ics_file" | "none";
 
 export function CalendarStep({ data, onUpdate, onNext, onBack }: CalendarStepProps) {
-  const [sourceType, setSourceType] = useState<SourceType>(data.source_type);
-  const [icsUrl, setIcsUrl] = useState(data.ics_url ?? "");
-  const [icsPath, setIcsPath] = useState(data.ics_path ?? "");
+  const existing = data.sources[0];
+  const [sourceType, setSourceType] = useState<SourceType>(
+    existing?.source_type === "ics_url" || existing?.source_type === "ics_file"
+      ? existing.source_type
+      : "none",
+  );
+  const [icsUrl, setIcsUrl] = useState(existing?.ics_url ?? "");
+  const [icsPath, setIcsPath] = useState(existing?.ics_path ?? "");
   const [saving, setSaving] = useState(false);
   const [error, setError] = useState<string | null>(null);
 
@@ -36,9 +41,25 @@ export function CalendarStep({ data, onUpdate, onNext, onBack }: CalendarStepPro
 
     try {
       const config: CalendarConfig = {
-        source_type: sourceType,
-        ics_url: sourceType === "ics_url" ? icsUrl : null,
-        ics_path: sourceType === "ics_file" ? icsPath : null,
+        ...data,
+        sources:
+          sourceType === "none"
+            ? []
+            : [
+                {
+                  id: existing?.id ?? "default",
+                  name: existing?.name ?? "Calendar",
+                  source_type: sourceType,
+                  ics_url: sourceType === "ics_url" ? icsUrl : null,
+                  ics_path: sourceType === "ics_file" ? icsPath : null,
+                  caldav_url: null,
+                  username: null,
+                  color: existing?.color ?? null,
+                  enabled: true,
+                  refresh_interval_minutes:
+                    existing?.refresh_interval_minutes ?? 5,
+                },
+              ],
       };
 
       await invoke("save_calendar_config", { config });
@@ -52,7 +73,7 @@ export function CalendarStep({ data, onUpdate, onNext, onBack }: CalendarStepPro
   };
 
   const skip = () => {
-    onUpdate({ source_type: "none", ics_url: null, ics_path: null });
+    onUpdate({ ...data, sources: [] });
     onNext();
   };
 
@@ -47,9 +47,15 @@ export function CompleteStep({ data, onComplete, onBack }: CompleteStepProps) {
             <CalendarIcon className="w-5 h-5 text-gray-400" />
             <span className="text-sm text-gray-200">Calendar</span>
           </div>
-          {data.calendar.source_type !== "none" ? (
+          {data.calendar.sources.length > 0 ? (
             <span className="text-sm text-green-400">
-              {data.calendar.source_type === "ics_url" ? "iCal URL" : "Local file"}
+              {data.calendar.sources[0].source_type === "ics_url"
+                ? "iCal URL"
+                : data.calendar.sources[0].source_type === "caldav"
+                  ? "CalDAV"
+                  : data.calendar.sources[0].source_type === "vdir"
+                    ? "Local directory"
+                    : "Local file"}
             </span>
           ) : (
             <span className="text-sm text-gray-500">Skipped</span>
@@ -1,8 +1,9 @@
 import { useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { Button, Input } from "../../components/ui";
+import { GitHubSignIn } from "../../components/github";
 import type { OnboardingData } from "./index";
-import type { GitHubUser } from "../../types";
+import type { GitHubAccount, GitHubUser } from "../../types";
 
 interface GitHubStepProps {
   data: OnboardingData["github"];
@@ -32,7 +33,7 @@ export function GitHubStep({
 
     try {
       const user = await invoke<GitHubUser>("validate_github_token", { token });
-      await invoke("save_github_token", { token });
+      await invoke("add_github_account", { name: "GitHub", token });
 
       onUpdate({ token, validated: true, user });
       onNext();
@@ -45,6 +46,15 @@ export function GitHubStep({
     }
   };
 
+  const signedIn = (account: GitHubAccount) => {
+    onUpdate({
+      token: "",
+      validated: true,
+      user: { login: account.username ?? account.name, avatar_url: "", name: null },
+    });
+    onNext();
+  };
+
   const skip = () => {
     onUpdate({ token: "", validated: false, user: null });
     onNext();
@@ -54,11 +64,19 @@ export function GitHubStep({
     <div>
       <h2 className="text-xl font-semibold text-white mb-2">Connect GitHub</h2>
       <p className="text-sm text-gray-400 mb-6">
-        Add your GitHub Personal Access Token to see PRs, issues, and
-        notifications.
+        Sign in with GitHub or add a Personal Access Token to see PRs, issues,
+        and notifications.
       </p>
 
       <div className="space-y-4 mb-6">
+        <GitHubSignIn
+          name="GitHub"
+          apiUrl={null}
+          graphqlUrl={null}
+          clientId={null}
+          onSignedIn={signedIn}
+        />
+
         <Input
           label="GitHub Personal Access Token"
           type="password"
@@ -24,7 +24,13 @@ export function Onboarding() {
   const [currentStep, setCurrentStep] = useState(0);
   const [data, setData] = useState<OnboardingData>({
     github: { token: "", validated: false, user: null },
-    calendar: { source_type: "none", ics_path: null, ics_url: null },
+    calendar: {
+      sources: [],
+      my_emails: [],
+      hide_declined: false,
+      reminders_enabled: true,
+      default_reminder_minutes: 5,
+    },
   });
 
   const nextStep = () => {
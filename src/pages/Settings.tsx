@@ -1,42 +1,191 @@
-import { useState } from "react";
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { format } from "date-fns";
 import { AppShell } from "../components/layout";
 import { Button, Card, Input } from "../components/ui";
+import { GitHubSignIn } from "../components/github";
 import { useSettingsStore } from "../store/settingsStore";
 import { useNavigate } from "react-router-dom";
+import type {
+  CalendarConfig,
+  CalendarSource,
+  CalendarSourceType,
+  GitHubAccount,
+  GitHubRateLimit,
+} from "../types";
 
 export function Settings() {
   const navigate = useNavigate();
-  const { settings, updateGitHubToken, updateCalendarConfig, clearCache } =
-    useSettingsStore();
+  const {
+    settings,
+    addGitHubAccount,
+    updateGitHubToken,
+    removeGitHubAccount,
+    updateCalendarConfig,
+    updateTimezone,
+    updateGitHubMaxItems,
+    saveCalendarCredential,
+    clearCache,
+  } = useSettingsStore();
 
   const [githubToken, setGithubToken] = useState("");
-  const [calendarUrl, setCalendarUrl] = useState(
-    settings?.calendar_config.ics_url ?? "",
-  );
-  const [calendarPath, setCalendarPath] = useState(
-    settings?.calendar_config.ics_path ?? "",
+  const [githubAccountName, setGithubAccountName] = useState("");
+  const [githubApiUrl, setGithubApiUrl] = useState("");
+  const [githubGraphqlUrl, setGithubGraphqlUrl] = useState("");
+  const [githubClientId, setGithubClientId] = useState("");
+  const [githubError, setGithubError] = useState<string | null>(null);
+  // Account whose token is being replaced; the form adds a new account otherwise
+  const [tokenAccountId, setTokenAccountId] = useState<string | null>(null);
+  const [sourceName, setSourceName] = useState("");
+  const [sourceColor, setSourceColor] = useState("#6366f1");
+  const [calendarUrl, setCalendarUrl] = useState("");
+  const [feedUsername, setFeedUsername] = useState("");
+  const [feedSecret, setFeedSecret] = useState("");
+  const [calendarPath, setCalendarPath] = useState("");
+  const [caldavUrl, setCaldavUrl] = useState("");
+  const [caldavUsername, setCaldavUsername] = useState("");
+  const [caldavPassword, setCaldavPassword] = useState("");
+  const [timezone, setTimezone] = useState(settings?.timezone ?? "");
+  const [myEmails, setMyEmails] = useState(
+    settings?.calendar_config.my_emails.join(", ") ?? "",
   );
+  const [timezoneError, setTimezoneError] = useState<string | null>(null);
   const [saving, setSaving] = useState(false);
 
+  const githubAccounts = settings?.github_accounts ?? [];
+  const tokenAccount = githubAccounts.find((a) => a.id === tokenAccountId) ?? null;
+
   const handleSaveGitHub = async () => {
     if (!githubToken.trim()) return;
     setSaving(true);
+    setGithubError(null);
     try {
-      await updateGitHubToken(githubToken);
+      if (tokenAccount) {
+        await updateGitHubToken(tokenAccount.id, githubToken);
+        setTokenAccountId(null);
+      } else {
+        await addGitHubAccount(
+          githubAccountName.trim() || "GitHub",
+          githubToken,
+          githubApiUrl.trim() || null,
+          githubGraphqlUrl.trim() || null,
+        );
+        setGithubAccountName("");
+        setGithubApiUrl("");
+        setGithubGraphqlUrl("");
+      }
       setGithubToken("");
+    } catch (err) {
+      setGithubError(String(err));
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  const handleGitHubSignedIn = () => {
+    setTokenAccountId(null);
+    setGithubAccountName("");
+    setGithubApiUrl("");
+    setGithubGraphqlUrl("");
+    setGithubClientId("");
+    setGithubError(null);
+  };
+
+  const handleRemoveGitHubAccount = async (id: string) => {
+    setSaving(true);
+    setGithubError(null);
+    try {
+      await removeGitHubAccount(id);
+    } catch (err) {
+      setGithubError(String(err));
     } finally {
       setSaving(false);
     }
   };
 
-  const handleSaveCalendar = async (type: "ics_url" | "ics_file") => {
+  const calendarConfig = settings?.calendar_config ?? {
+    sources: [],
+    my_emails: [],
+    hide_declined: false,
+    reminders_enabled: true,
+    default_reminder_minutes: 5,
+  };
+  const sources = calendarConfig.sources;
+
+  const saveCalendarConfig = async (changes: Partial<CalendarConfig>) => {
     setSaving(true);
     try {
-      await updateCalendarConfig({
+      await updateCalendarConfig({ ...calendarConfig, ...changes });
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  const saveSources = (next: CalendarSource[]) =>
+    saveCalendarConfig({ sources: next });
+
+  const handleSaveEmails = () =>
+    saveCalendarConfig({
+      my_emails: myEmails
+        .split(",")
+        .map((email) => email.trim())
+        .filter(Boolean),
+    });
+
+  const handleAddSource = async (type: CalendarSourceType) => {
+    const id = crypto.randomUUID();
+    if (type === "caldav" && caldavPassword) {
+      await saveCalendarCredential(id, caldavPassword);
+    }
+    if (type === "ics_url" && feedSecret) {
+      await saveCalendarCredential(id, feedSecret);
+    }
+    await saveSources([
+      ...sources,
+      {
+        id,
+        name: sourceName.trim() || "Calendar",
         source_type: type,
-        ics_url: type === "ics_url" ? calendarUrl : null,
-        ics_path: type === "ics_file" ? calendarPath : null,
-      });
+        ics_url: type === "ics_url" ? calendarUrl.trim() : null,
+        ics_path:
+          type === "ics_file" || type === "vdir" ? calendarPath.trim() : null,
+        caldav_url: type === "caldav" ? caldavUrl.trim() : null,
+        username:
+          type === "caldav"
+            ? caldavUsername.trim() || null
+            : type === "ics_url"
+              ? feedUsername.trim() || null
+              : null,
+        color: sourceColor,
+        enabled: true,
+        refresh_interval_minutes: 5,
+      },
+    ]);
+    setSourceName("");
+    setCalendarUrl("");
+    setFeedUsername("");
+    setFeedSecret("");
+    setCalendarPath("");
+    setCaldavUrl("");
+    setCaldavUsername("");
+    setCaldavPassword("");
+  };
+
+  const handleToggleSource = (id: string) =>
+    saveSources(
+      sources.map((s) => (s.id === id ? { ...s, enabled: !s.enabled } : s)),
+    );
+
+  const handleRemoveSource = (id: string) =>
+    saveSources(sources.filter((s) => s.id !== id));
+
+  const handleSaveTimezone = async () => {
+    setSaving(true);
+    setTimezoneError(null);
+    try {
+      await updateTimezone(timezone.trim());
+    } catch (err) {
+      setTimezoneError(String(err));
     } finally {
       setSaving(false);
     }
@@ -68,32 +217,147 @@ export function Settings() {
           </h2>
 
           <div className="space-y-3">
-            <div className="flex items-center gap-2 text-sm">
-              <span className="text-gray-400">Status:</span>
-              {settings?.github_configured ? (
-                <span className="text-green-400">
-                  Connected as @{settings.github_username}
-                </span>
-              ) : (
-                <span className="text-yellow-400">Not configured</span>
-              )}
-            </div>
+            {githubAccounts.length === 0 ? (
+              <p className="text-sm text-yellow-400">Not configured</p>
+            ) : (
+              <div className="space-y-2">
+                {githubAccounts.map((account) => (
+                  <div
+                    key={account.id}
+                    className="flex items-center gap-3 p-2 bg-gray-900 border border-gray-800 rounded-lg"
+                  >
+                    <div className="flex-1 min-w-0">
+                      <p className="text-sm text-gray-200 truncate">
+                        {account.name}
+                        {account.username && (
+                          <span className="text-green-400"> @{account.username}</span>
+                        )}
+                      </p>
+                      <p className="text-xs text-gray-500 truncate">{account.endpoints.api_url}</p>
+                      {account.token_expires_at &&
+                        account.token_expires_at * 1000 < Date.now() && (
+                          <p className="text-xs text-yellow-400">Sign-in expired, sign in again</p>
+                        )}
+                    </div>
+                    <Button
+                      size="sm"
+                      variant="secondary"
+                      onClick={() => setTokenAccountId(account.id)}
+                      disabled={saving}
+                    >
+                      Update Token
+                    </Button>
+                    <Button
+                      size="sm"
+                      variant="secondary"
+                      onClick={() => handleRemoveGitHubAccount(account.id)}
+                      disabled={saving}
+                    >
+                      Remove
+                    </Button>
+                  </div>
+                ))}
+              </div>
+            )}
+
+            {tokenAccount ? (
+              <p className="text-sm text-gray-400">
+                New token for {tokenAccount.name}{" "}
+                <button
+                  className="text-gray-500 hover:text-gray-300 underline"
+                  onClick={() => setTokenAccountId(null)}
+                >
+                  cancel
+                </button>
+              </p>
+            ) : (
+              <div className="space-y-2">
+                <Input
+                  placeholder="Account name (e.g. Personal, Work)"
+                  value={githubAccountName}
+                  onChange={(e) => setGithubAccountName(e.target.value)}
+                />
+                <Input
+                  placeholder="Server: github.com or a GitHub Enterprise Server address"
+                  value={githubApiUrl}
+                  onChange={(e) => setGithubApiUrl(e.target.value)}
+                />
+                <Input
+                  placeholder="GraphQL endpoint (optional, derived from the server)"
+                  value={githubGraphqlUrl}
+                  onChange={(e) => setGithubGraphqlUrl(e.target.value)}
+                />
+                <Input
+                  placeholder="OAuth app client ID (optional on github.com)"
+                  value={githubClientId}
+                  onChange={(e) => setGithubClientId(e.target.value)}
+                />
+                <p className="text-xs text-gray-500">
+                  Builds for github.com can come with an OAuth app. To use your own, or to sign
+                  in to Enterprise Server, register an OAuth app under Settings &gt; Developer
+                  settings &gt; OAuth Apps, enable device flow, and enter its client ID.
+                </p>
+              </div>
+            )}
+
+            {tokenAccount ? (
+              <GitHubSignIn
+                key={tokenAccount.id}
+                name={tokenAccount.name}
+                apiUrl={tokenAccount.endpoints.api_url}
+                graphqlUrl={tokenAccount.endpoints.graphql_url}
+                clientId={tokenAccount.oauth_client_id}
+                label="Sign in again with GitHub"
+                onSignedIn={handleGitHubSignedIn}
+              />
+            ) : (
+              <GitHubSignIn
+                name={githubAccountName.trim() || "GitHub"}
+                apiUrl={githubApiUrl.trim() || null}
+                graphqlUrl={githubGraphqlUrl.trim() || null}
+                clientId={githubClientId.trim() || null}
+                onSignedIn={handleGitHubSignedIn}
+              />
+            )}
+
+            <p className="text-xs text-gray-500">Or use a personal access token:</p>
 
             <Input
               type="password"
-              placeholder="Enter new GitHub token to update..."
+              placeholder="GitHub token"
               value={githubToken}
               onChange={(e) => setGithubToken(e.target.value)}
             />
 
+            {githubError && <p className="text-xs text-red-400">{githubError}</p>}
+
             <Button
               size="sm"
               onClick={handleSaveGitHub}
               disabled={!githubToken.trim() || saving}
               loading={saving}
             >
-              Update Token
+              {tokenAccount ? "Save Token" : "Add Account"}
             </Button>
+
+            <div className="flex items-center gap-2 text-sm text-gray-400">
+              <span>Fetch up to</span>
+              <input
+                type="number"
+                min={1}
+                max={1000}
+                value={settings?.github_max_items ?? 100}
+                onChange={(e) =>
+                  updateGitHubMaxItems(
+                    Math.min(1000, Math.max(1, Number(e.target.value) || 1)),
+                  )
+                }
+                className="w-20 px-2 py-1 bg-gray-800 border border-gray-700 rounded-lg text-gray-200"
+              />
+              <span>items per list</span>
+            </div>
+
+            {githubAccounts.length > 0 && <GitHubRateLimits accounts={githubAccounts} />}
           </div>
         </Card>
 
@@ -104,53 +368,254 @@ export function Settings() {
           </h2>
 
           <div className="space-y-4">
-            <div className="flex items-center gap-2 text-sm">
-              <span className="text-gray-400">Source:</span>
-              <span className="text-gray-200">
-                {settings?.calendar_config.source_type === "ics_url" &&
-                  "iCal URL"}
-                {settings?.calendar_config.source_type === "ics_file" &&
-                  "Local .ics file"}
-                {settings?.calendar_config.source_type === "none" &&
-                  "Not configured"}
-              </span>
+            {sources.length === 0 ? (
+              <p className="text-sm text-yellow-400">Not configured</p>
+            ) : (
+              <div className="space-y-2">
+                {sources.map((source) => (
+                  <div
+                    key={source.id}
+                    className="flex items-center gap-3 p-2 bg-gray-900 border border-gray-800 rounded-lg"
+                  >
+                    <span
+                      className="w-3 h-3 rounded-full flex-shrink-0"
+                      style={{ backgroundColor: source.color ?? "#6b7280" }}
+                    />
+                    <div className="flex-1 min-w-0">
+                      <p
+                        className={`text-sm truncate ${source.enabled ? "text-gray-200" : "text-gray-500"}`}
+                      >
+                        {source.name}
+                      </p>
+                      <p className="text-xs text-gray-500 truncate">
+                        {source.source_type === "ics_url"
+                          ? source.ics_url
+                          : source.source_type === "caldav"
+                            ? source.caldav_url
+                            : source.ics_path}
+                      </p>
+                    </div>
+                    <Button
+                      size="sm"
+                      variant="secondary"
+                      onClick={() => handleToggleSource(source.id)}
+                      disabled={saving}
+                    >
+                      {source.enabled ? "Disable" : "Enable"}
+                    </Button>
+                    <Button
+                      size="sm"
+                      variant="secondary"
+                      onClick={() => handleRemoveSource(source.id)}
+                      disabled={saving}
+                    >
+                      Remove
+                    </Button>
+                  </div>
+                ))}
+              </div>
+            )}
+
+            <div className="border-t border-gray-800 pt-4 flex gap-3">
+              <Input
+                label="Name"
+                type="text"
+                placeholder="Work"
+                value={sourceName}
+                onChange={(e) => setSourceName(e.target.value)}
+              />
+              <div>
+                <label className="block text-sm font-medium text-gray-300 mb-1.5">
+                  Colour
+                </label>
+                <input
+                  type="color"
+                  value={sourceColor}
+                  onChange={(e) => setSourceColor(e.target.value)}
+                  className="h-[42px] w-12 bg-gray-800 border border-gray-700 rounded-lg"
+                />
+              </div>
             </div>
 
-            <div>
+            <div className="space-y-3">
               <Input
                 label="iCal URL"
                 type="url"
-                placeholder="https://calendar.google.com/calendar/ical/..."
+                placeholder="https://calendar.google.com/calendar/ical/... or webcal://..."
                 value={calendarUrl}
                 onChange={(e) => setCalendarUrl(e.target.value)}
               />
+              <Input
+                label="Username (optional)"
+                type="text"
+                placeholder="Leave empty to send a bearer token"
+                value={feedUsername}
+                onChange={(e) => setFeedUsername(e.target.value)}
+              />
+              <Input
+                label="Password or token (optional)"
+                type="password"
+                value={feedSecret}
+                onChange={(e) => setFeedSecret(e.target.value)}
+              />
               <Button
                 size="sm"
                 variant="secondary"
-                className="mt-2"
-                onClick={() => handleSaveCalendar("ics_url")}
+                onClick={() => handleAddSource("ics_url")}
                 disabled={!calendarUrl.trim() || saving}
               >
-                Save URL
+                Add URL
               </Button>
             </div>
 
             <div className="border-t border-gray-800 pt-4">
               <Input
-                label="Local .ics file path"
+                label="Local .ics file or vdir directory"
                 type="text"
-                placeholder="/path/to/calendar.ics"
+                placeholder="/path/to/calendar.ics or /path/to/vdir/work"
                 value={calendarPath}
                 onChange={(e) => setCalendarPath(e.target.value)}
               />
+              <div className="mt-2 flex gap-2">
+                <Button
+                  size="sm"
+                  variant="secondary"
+                  onClick={() => handleAddSource("ics_file")}
+                  disabled={!calendarPath.trim() || saving}
+                >
+                  Add File
+                </Button>
+                <Button
+                  size="sm"
+                  variant="secondary"
+                  onClick={() => handleAddSource("vdir")}
+                  disabled={!calendarPath.trim() || saving}
+                >
+                  Add Directory
+                </Button>
+              </div>
+            </div>
+
+            <div className="border-t border-gray-800 pt-4 space-y-3">
+              <Input
+                label="CalDAV server URL"
+                type="url"
+                placeholder="https://caldav.fastmail.com/"
+                value={caldavUrl}
+                onChange={(e) => setCaldavUrl(e.target.value)}
+              />
+              <Input
+                label="Username"
+                type="text"
+                value={caldavUsername}
+                onChange={(e) => setCaldavUsername(e.target.value)}
+              />
+              <Input
+                label="Password"
+                type="password"
+                placeholder="App-specific password"
+                value={caldavPassword}
+                onChange={(e) => setCaldavPassword(e.target.value)}
+              />
+              <Button
+                size="sm"
+                variant="secondary"
+                onClick={() => handleAddSource("caldav")}
+                disabled={!caldavUrl.trim() || saving}
+              >
+                Add CalDAV
+              </Button>
+            </div>
+
+            <div className="border-t border-gray-800 pt-4">
+              <Input
+                label="My email addresses"
+                type="text"
+                placeholder="me@work.com, me@personal.com"
+                value={myEmails}
+                onChange={(e) => setMyEmails(e.target.value)}
+              />
+              <p className="text-xs text-gray-500 mt-1">
+                Used to find your RSVP on invitations.
+              </p>
               <Button
                 size="sm"
                 variant="secondary"
                 className="mt-2"
-                onClick={() => handleSaveCalendar("ics_file")}
-                disabled={!calendarPath.trim() || saving}
+                onClick={handleSaveEmails}
+                disabled={saving}
               >
-                Save Path
+                Save Addresses
+              </Button>
+              <label className="flex items-center gap-2 mt-3 text-sm text-gray-300">
+                <input
+                  type="checkbox"
+                  checked={calendarConfig.hide_declined}
+                  onChange={(e) =>
+                    saveCalendarConfig({ hide_declined: e.target.checked })
+                  }
+                  disabled={saving}
+                />
+                Hide meetings I've declined
+              </label>
+            </div>
+
+            <div className="border-t border-gray-800 pt-4">
+              <label className="flex items-center gap-2 text-sm text-gray-300">
+                <input
+                  type="checkbox"
+                  checked={calendarConfig.reminders_enabled}
+                  onChange={(e) =>
+                    saveCalendarConfig({ reminders_enabled: e.target.checked })
+                  }
+                  disabled={saving}
+                />
+                Desktop reminders before meetings
+              </label>
+              <div className="flex items-center gap-2 mt-2 text-sm text-gray-400">
+                <span>Remind me</span>
+                <input
+                  type="number"
+                  min={0}
+                  value={calendarConfig.default_reminder_minutes}
+                  onChange={(e) =>
+                    saveCalendarConfig({
+                      default_reminder_minutes: Math.max(
+                        0,
+                        Number(e.target.value) || 0,
+                      ),
+                    })
+                  }
+                  disabled={saving || !calendarConfig.reminders_enabled}
+                  className="w-16 px-2 py-1 bg-gray-800 border border-gray-700 rounded-lg text-gray-200"
+                />
+                <span>minutes before events without their own alarm</span>
+              </div>
+            </div>
+
+            <div className="border-t border-gray-800 pt-4">
+              <Input
+                label="Timezone"
+                type="text"
+                placeholder="Europe/Berlin"
+                value={timezone}
+                onChange={(e) => setTimezone(e.target.value)}
+              />
+              <p className="text-xs text-gray-500 mt-1">
+                IANA name used to decide what "today" means. Defaults to your
+                system timezone.
+              </p>
+              {timezoneError && (
+                <p className="text-xs text-red-400 mt-1">{timezoneError}</p>
+              )}
+              <Button
+                size="sm"
+                variant="secondary"
+                className="mt-2"
+                onClick={handleSaveTimezone}
+                disabled={!timezone.trim() || saving}
+              >
+                Save Timezone
               </Button>
             </div>
           </div>
@@ -189,3 +654,30 @@ export function Settings() {
     </AppShell>
   );
 }
+
+function GitHubRateLimits({ accounts }: { accounts: GitHubAccount[] }) {
+  const [limits, setLimits] = useState<GitHubRateLimit[]>([]);
+
+  useEffect(() => {
+    invoke<GitHubRateLimit[]>("get_github_rate_limits")
+      .then(setLimits)
+      .catch(() => setLimits([]));
+  }, []);
+
+  // The brief only draws on these budgets
+  const shown = limits.filter((l) => ["core", "search", "graphql"].includes(l.resource));
+  if (shown.length === 0) return null;
+
+  return (
+    <div className="text-xs text-gray-500 space-y-1">
+      {shown.map((limit) => (
+        <div key={`${limit.account}:${limit.resource}`}>
+          {accounts.length > 1 &&
+            `${accounts.find((a) => a.id === limit.account)?.name ?? limit.account} · `}
+          API budget ({limit.resource}): {limit.remaining} of {limit.limit} left, resets at{" "}
+          {format(new Date(limit.reset * 1000), "HH:mm")}
+        </div>
+      ))}
+    </div>
+  );
+}
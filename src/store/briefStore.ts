@@ -1,6 +1,6 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
-import type { BriefData } from "../types";
+import type { BriefData, GitHubNotification } from "../types";
 
 interface BriefStore {
   brief: BriefData | null;
@@ -11,45 +11,105 @@ interface BriefStore {
 
   loadBrief: () => Promise<void>;
   refreshBrief: () => Promise<void>;
+  markNotificationRead: (notification: GitHubNotification) => Promise<void>;
+  markNotificationDone: (notification: GitHubNotification) => Promise<void>;
+  markRepoNotificationsRead: (notification: GitHubNotification) => Promise<void>;
+  unsubscribeNotification: (notification: GitHubNotification) => Promise<void>;
   clearError: () => void;
 }
 
-export const useBriefStore = create<BriefStore>((set) => ({
-  brief: null,
-  loading: false,
-  refreshing: false,
-  error: null,
-  lastUpdated: null,
-
-  loadBrief: async () => {
-    set({ loading: true, error: null });
-    try {
-      const brief = await invoke<BriefData>("get_brief");
+export const useBriefStore = create<BriefStore>((set, get) => {
+  // Shows the change immediately and rolls it back if GitHub rejects it
+  const triage = async (
+    update: (notifications: GitHubNotification[]) => GitHubNotification[],
+    command: string,
+    args: Record<string, string>,
+  ) => {
+    const brief = get().brief;
+    if (brief?.github) {
       set({
-        brief,
-        loading: false,
-        lastUpdated: brief.generated_at
-          ? new Date(brief.generated_at * 1000)
-          : null,
+        brief: {
+          ...brief,
+          github: { ...brief.github, notifications: update(brief.github.notifications) },
+        },
       });
-    } catch (err) {
-      set({ loading: false, error: String(err) });
     }
-  },
-
-  refreshBrief: async () => {
-    set({ refreshing: true, error: null });
     try {
-      const brief = await invoke<BriefData>("refresh_brief");
-      set({
-        brief,
-        refreshing: false,
-        lastUpdated: new Date(),
-      });
+      await invoke(command, args);
     } catch (err) {
-      set({ refreshing: false, error: String(err) });
+      set({ brief, error: String(err) });
     }
-  },
+  };
+
+  const markRead = (matches: (n: GitHubNotification) => boolean) =>
+    (notifications: GitHubNotification[]) =>
+      notifications.map((n) => (matches(n) ? { ...n, unread: false } : n));
+
+  return {
+    brief: null,
+    loading: false,
+    refreshing: false,
+    error: null,
+    lastUpdated: null,
+
+    loadBrief: async () => {
+      set({ loading: true, error: null });
+      try {
+        const brief = await invoke<BriefData>("get_brief");
+        set({
+          brief,
+          loading: false,
+          lastUpdated: brief.generated_at
+            ? new Date(brief.generated_at * 1000)
+            : null,
+        });
+      } catch (err) {
+        set({ loading: false, error: String(err) });
+      }
+    },
+
+    refreshBrief: async () => {
+      set({ refreshing: true, error: null });
+      try {
+        const brief = await invoke<BriefData>("refresh_brief");
+        set({
+          brief,
+          refreshing: false,
+          lastUpdated: new Date(),
+        });
+      } catch (err) {
+        set({ refreshing: false, error: String(err) });
+      }
+    },
+
+    markNotificationRead: ({ account, id }) =>
+      triage(
+        markRead((n) => n.account === account && n.id === id),
+        "mark_github_notification_read",
+        { accountId: account, threadId: id },
+      ),
+
+    markNotificationDone: ({ account, id }) =>
+      triage(
+        (notifications) => notifications.filter((n) => !(n.account === account && n.id === id)),
+        "mark_github_notification_done",
+        { accountId: account, threadId: id },
+      ),
+
+    markRepoNotificationsRead: ({ account, repo_full_name }) =>
+      triage(
+        markRead((n) => n.account === account && n.repo_full_name === repo_full_name),
+        "mark_github_repo_notifications_read",
+        { accountId: account, repoFullName: repo_full_name },
+      ),
+
+    unsubscribeNotification: ({ account, id }) =>
+      triage(
+        markRead((n) => n.account === account && n.id === id),
+        "unsubscribe_github_notification",
+        { accountId: account, threadId: id },
+      ),
 
-  clearError: () => set({ error: null }),
-}));
+    clearError: () => set({ error: null }),
+  };
+});
@@ -1,6 +1,12 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
-import type { AllSettings, CalendarConfig } from "../types";
+import type {
+  AllSettings,
+  CalendarConfig,
+  GitHubAccount,
+  GitHubDeviceCode,
+  GitHubDeviceFlowPoll,
+} from "../types";
 
 interface SettingsStore {
   settings: AllSettings | null;
@@ -8,8 +14,30 @@ interface SettingsStore {
   error: string | null;
 
   loadSettings: () => Promise<void>;
-  updateGitHubToken: (token: string) => Promise<void>;
+  addGitHubAccount: (
+    name: string,
+    token: string,
+    apiUrl: string | null,
+    graphqlUrl: string | null,
+  ) => Promise<void>;
+  updateGitHubToken: (accountId: string, token: string) => Promise<void>;
+  startGitHubDeviceFlow: (
+    apiUrl: string | null,
+    graphqlUrl: string | null,
+    clientId: string | null,
+  ) => Promise<GitHubDeviceCode>;
+  pollGitHubDeviceFlow: (
+    name: string,
+    deviceCode: string,
+    apiUrl: string | null,
+    graphqlUrl: string | null,
+    clientId: string | null,
+  ) => Promise<GitHubDeviceFlowPoll>;
+  removeGitHubAccount: (accountId: string) => Promise<void>;
   updateCalendarConfig: (config: CalendarConfig) => Promise<void>;
+  updateTimezone: (timezone: string) => Promise<void>;
+  updateGitHubMaxItems: (maxItems: number) => Promise<void>;
+  saveCalendarCredential: (sourceId: string, secret: string) => Promise<void>;
   clearCache: () => Promise<void>;
   markOnboardingComplete: () => Promise<void>;
 }
@@ -29,10 +57,19 @@ export const useSettingsStore = create<SettingsStore>((set, get) => ({
     }
   },
 
-  updateGitHubToken: async (token: string) => {
+  addGitHubAccount: async (
+    name: string,
+    token: string,
+    apiUrl: string | null,
+    graphqlUrl: string | null,
+  ) => {
     try {
-      const user = await invoke<{ login: string }>("validate_github_token", { token });
-      await invoke("save_github_token", { token });
+      const account = await invoke<GitHubAccount>("add_github_account", {
+        name,
+        token,
+        apiUrl,
+        graphqlUrl,
+      });
 
       const currentSettings = get().settings;
       if (currentSettings) {
@@ -40,15 +77,96 @@ export const useSettingsStore = create<SettingsStore>((set, get) => ({
           settings: {
             ...currentSettings,
             github_configured: true,
-            github_username: user.login,
+            github_accounts: [...currentSettings.github_accounts, account],
           },
         });
       }
+    } catch (err) {
+      throw new Error(`Failed to add GitHub account: ${err}`);
+    }
+  },
+
+  updateGitHubToken: async (accountId: string, token: string) => {
+    try {
+      await invoke("save_github_token", { accountId, token });
     } catch (err) {
       throw new Error(`Failed to update GitHub token: ${err}`);
     }
   },
 
+  startGitHubDeviceFlow: async (
+    apiUrl: string | null,
+    graphqlUrl: string | null,
+    clientId: string | null,
+  ) => {
+    try {
+      return await invoke<GitHubDeviceCode>("start_github_device_flow", {
+        apiUrl,
+        graphqlUrl,
+        clientId,
+      });
+    } catch (err) {
+      throw new Error(`Failed to start GitHub sign-in: ${err}`);
+    }
+  },
+
+  pollGitHubDeviceFlow: async (
+    name: string,
+    deviceCode: string,
+    apiUrl: string | null,
+    graphqlUrl: string | null,
+    clientId: string | null,
+  ) => {
+    try {
+      const result = await invoke<GitHubDeviceFlowPoll>("poll_github_device_flow", {
+        name,
+        deviceCode,
+        apiUrl,
+        graphqlUrl,
+        clientId,
+      });
+
+      const account = result.account;
+      const currentSettings = get().settings;
+      if (account && currentSettings) {
+        // Signing in again replaces the account that was already there
+        const accounts = currentSettings.github_accounts;
+        set({
+          settings: {
+            ...currentSettings,
+            github_configured: true,
+            github_accounts: accounts.some((a) => a.id === account.id)
+              ? accounts.map((a) => (a.id === account.id ? account : a))
+              : [...accounts, account],
+          },
+        });
+      }
+      return result;
+    } catch (err) {
+      throw new Error(`GitHub sign-in failed: ${err}`);
+    }
+  },
+
+  removeGitHubAccount: async (accountId: string) => {
+    try {
+      await invoke("remove_github_account", { accountId });
+
+      const currentSettings = get().settings;
+      if (currentSettings) {
+        const accounts = currentSettings.github_accounts.filter((a) => a.id !== accountId);
+        set({
+          settings: {
+            ...currentSettings,
+            github_configured: accounts.length > 0,
+            github_accounts: accounts,
+          },
+        });
+      }
+    } catch (err) {
+      throw new Error(`Failed to remove GitHub account: ${err}`);
+    }
+  },
+
   updateCalendarConfig: async (config: CalendarConfig) => {
     try {
       await invoke("save_calendar_config", { config });
@@ -67,6 +185,50 @@ export const useSettingsStore = create<SettingsStore>((set, get) => ({
     }
   },
 
+  updateTimezone: async (timezone: string) => {
+    try {
+      await invoke("save_timezone", { timezone });
+
+      const currentSettings = get().settings;
+      if (currentSettings) {
+        set({
+          settings: {
+            ...currentSettings,
+            timezone,
+          },
+        });
+      }
+    } catch (err) {
+      throw new Error(`Failed to update timezone: ${err}`);
+    }
+  },
+
+  updateGitHubMaxItems: async (maxItems: number) => {
+    try {
+      await invoke("save_github_max_items", { maxItems });
+
+      const currentSettings = get().settings;
+      if (currentSettings) {
+        set({
+          settings: {
+            ...currentSettings,
+            github_max_items: maxItems,
+          },
+        });
+      }
+    } catch (err) {
+      throw new Error(`Failed to update GitHub item limit: ${err}`);
+    }
+  },
+
+  saveCalendarCredential: async (sourceId: string, secret: string) => {
+    try {
+      await invoke("save_calendar_credential", { sourceId, secret });
+    } catch (err) {
+      throw new Error(`Failed to save calendar credential: ${err}`);
+    }
+  },
+
   clearCache: async () => {
     try {
       await invoke("clear_cache");
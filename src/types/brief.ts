@@ -1,10 +1,21 @@
 import type { GitHubBriefData } from "./github";
-import type { CalendarEvent } from "./calendar";
+import type {
+  CalendarConflict,
+  CalendarEvent,
+  CalendarTask,
+  DaySchedule,
+  Lookahead,
+} from "./calendar";
 import type { EmailHeader } from "./email";
 
 export interface BriefData {
   github: GitHubBriefData | null;
   calendar: CalendarEvent[];
+  pending_invitations: CalendarEvent[];
+  schedule: DaySchedule;
+  conflicts: CalendarConflict[];
+  tasks: CalendarTask[];
+  lookahead: Lookahead;
   email: EmailHeader[];
   generated_at: number;
 }
@@ -1,5 +1,6 @@
 export interface CalendarEvent {
   id: string;
+  source: string;
   summary: string;
   description: string | null;
   location: string | null;
@@ -7,12 +8,115 @@ export interface CalendarEvent {
   end_time: number;
   all_day: boolean;
   html_link: string | null;
+  join_url: string | null;
+  conference_provider: ConferenceProvider | null;
+  organizer: EventAttendee | null;
+  attendees: EventAttendee[];
+  my_status: ParticipationStatus | null;
+  out_of_office: boolean;
+  reminder_offsets: number[];
+  color: string | null;
   is_now: boolean;
   is_soon: boolean;
 }
 
-export interface CalendarConfig {
-  source_type: "ics_file" | "ics_url" | "none";
+export type ConferenceProvider =
+  | "zoom"
+  | "google_meet"
+  | "teams"
+  | "jitsi"
+  | "webex";
+
+export type ParticipationStatus =
+  | "needs_action"
+  | "accepted"
+  | "declined"
+  | "tentative"
+  | "delegated";
+
+export interface CalendarTask {
+  id: string;
+  source: string;
+  summary: string;
+  description: string | null;
+  due_time: number | null;
+  due_all_day: boolean;
+  priority: number | null;
+  status: "needs_action" | "in_process";
+  percent_complete: number | null;
+  color: string | null;
+  is_overdue: boolean;
+}
+
+export interface EventAttendee {
+  email: string;
+  name: string | null;
+  role: string | null;
+  status: ParticipationStatus | null;
+}
+
+export interface TimeSlot {
+  start: number;
+  end: number;
+  duration_minutes: number;
+}
+
+export interface BackToBackChain {
+  start: number;
+  end: number;
+  event_ids: string[];
+}
+
+export interface CalendarConflict {
+  kind: "double_booking" | "out_of_office";
+  start: number;
+  end: number;
+  event_ids: string[];
+  summaries: string[];
+}
+
+export interface DaySchedule {
+  work_start: number;
+  work_end: number;
+  meeting_count: number;
+  meeting_minutes: number;
+  free_gaps: TimeSlot[];
+  back_to_back: BackToBackChain[];
+  longest_focus_block: TimeSlot | null;
+  next_free: TimeSlot | null;
+}
+
+export interface Lookahead {
+  next_day_start: number;
+  first_meeting: CalendarEvent | null;
+  meeting_count: number;
+  deadlines: CalendarTask[];
+}
+
+export type CalendarSourceType = "ics_file" | "ics_url" | "caldav" | "vdir";
+
+export interface CalendarSource {
+  id: string;
+  name: string;
+  source_type: CalendarSourceType;
   ics_path: string | null;
   ics_url: string | null;
+  caldav_url: string | null;
+  username: string | null;
+  color: string | null;
+  enabled: boolean;
+  refresh_interval_minutes: number;
+}
+
+export interface CalendarConfig {
+  sources: CalendarSource[];
+  my_emails: string[];
+  hide_declined: boolean;
+  reminders_enabled: boolean;
+  default_reminder_minutes: number;
+}
+
+export interface MeetingReminder {
+  event: CalendarEvent;
+  remind_at: number;
 }
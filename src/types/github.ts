@@ -11,6 +11,7 @@ export interface GitHubLabel {
 
 export interface GitHubPullRequest {
   id: number;
+  account: string;
   repo_full_name: string;
   number: number;
   title: string;
@@ -23,11 +24,14 @@ export interface GitHubPullRequest {
   updated_at: string;
   requested_reviewers: string[];
   labels: GitHubLabel[];
-  review_status: string | null;
+  review_status: "approved" | "changes_requested" | "pending" | null;
+  ci_status: "success" | "failure" | "pending" | null;
+  mergeable: "mergeable" | "conflicting" | "unknown" | null;
 }
 
 export interface GitHubIssue {
   id: number;
+  account: string;
   repo_full_name: string;
   number: number;
   title: string;
@@ -40,6 +44,7 @@ export interface GitHubIssue {
 
 export interface GitHubNotification {
   id: string;
+  account: string;
   repo_full_name: string;
   subject_title: string;
   subject_type: string;
@@ -49,10 +54,62 @@ export interface GitHubNotification {
   url: string | null;
 }
 
+export interface GitHubTotals {
+  prs_to_review: number;
+  my_open_prs: number;
+  mentioned_issues: number;
+}
+
+export interface GitHubAccount {
+  id: string;
+  name: string;
+  username: string | null;
+  endpoints: GitHubEndpoints;
+  oauth_client_id: string | null;
+  token_expires_at: number | null;
+}
+
+export interface GitHubDeviceCode {
+  device_code: string;
+  user_code: string;
+  verification_uri: string;
+  expires_in: number;
+  interval: number;
+}
+
+export interface GitHubDeviceFlowPoll {
+  account: GitHubAccount | null;
+  interval: number | null;
+}
+
+export interface GitHubAccountSummary {
+  id: string;
+  name: string;
+  username: string | null;
+  totals: GitHubTotals;
+}
+
 export interface GitHubBriefData {
   prs_to_review: GitHubPullRequest[];
   my_open_prs: GitHubPullRequest[];
   mentioned_issues: GitHubIssue[];
   notifications: GitHubNotification[];
+  totals: GitHubTotals;
+  accounts: GitHubAccountSummary[];
   last_updated: number | null;
+  rate_limited_until: number | null;
+}
+
+export interface GitHubEndpoints {
+  api_url: string;
+  graphql_url: string;
+}
+
+export interface GitHubRateLimit {
+  account: string;
+  resource: string;
+  limit: number;
+  remaining: number;
+  used: number;
+  reset: number;
 }
@@ -1,11 +1,14 @@
 import type { CalendarConfig } from "./calendar";
 import type { EmailConfig } from "./email";
+import type { GitHubAccount } from "./github";
 
 export interface AllSettings {
   github_configured: boolean;
-  github_username: string | null;
+  github_accounts: GitHubAccount[];
+  github_max_items: number;
   calendar_config: CalendarConfig;
   email_config: EmailConfig;
+  timezone: string;
   onboarding_complete: boolean;
 }
 